@@ -1,5 +1,5 @@
 use rustest::*;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, PoisonError};
 /// Transform a fixture into a global fixture.
 ///
 /// ```rust
@@ -39,6 +39,7 @@ where
 impl<Source> Fixture for Global<Source>
 where
     Source: SubFixture,
+    Proxy<Source>: Send,
 {
     type Type = Source::Type;
     type Proxy = Proxy<Source>;
@@ -47,6 +48,7 @@ where
 impl<Source> ::std::ops::Deref for Global<Source>
 where
     Source: SubFixture,
+    Proxy<Source>: Send,
 {
     type Target = <Self as ::rustest::Fixture>::Type;
     fn deref(&self) -> &Self::Target {
@@ -88,7 +90,10 @@ where
     }
 }
 
-impl<Source: SubFixture> FixtureProxy for Proxy<Source> {
+impl<Source: SubFixture> FixtureProxy for Proxy<Source>
+where
+    Self: Send,
+{
     type Fixt = Global<Source>;
     const SCOPE: FixtureScope = FixtureScope::Global;
 
@@ -111,8 +116,21 @@ impl<Source: SubFixture> FixtureProxy for Proxy<Source> {
         let inner = self
             .inner
             .lock()
-            .unwrap()
+            .unwrap_or_else(PoisonError::into_inner)
             .get(|CallArgs((source,))| Ok((source, None)))?;
         Ok(Global(inner))
     }
+
+    fn tags() -> Vec<&'static str> {
+        Source::Proxy::tags()
+    }
+
+    fn plan(&self) -> FixturePlan {
+        let plan =
+            FixturePlan::of(self).with_shared(Arc::as_ptr(&self.inner) as *const () as usize);
+        match &*self.inner.lock().unwrap_or_else(PoisonError::into_inner) {
+            LazyValue::Proxies(Some(proxies)) => plan.with_dependencies(proxies.plan()),
+            _ => plan,
+        }
+    }
 }
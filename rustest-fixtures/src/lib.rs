@@ -1,3 +1,4 @@
+#![allow(clippy::test_attr_in_doctest)]
 //! This crate is a set of standard fixtures for rustest.
 //!
 //! This crate is pretty young and a the number of fixture is small.
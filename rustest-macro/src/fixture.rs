@@ -6,7 +6,9 @@ use syn::{
     spanned::Spanned,
 };
 
-use crate::utils::{FixtureInfo, gen_fixture_call, gen_param_fixture, to_call_args, to_tuple};
+use crate::utils::{
+    FixtureInfo, gen_fixture_call, gen_param_fixture, gen_tags, parse_tags, to_call_args, to_tuple,
+};
 
 #[derive(Debug, PartialEq, Copy, Clone)]
 enum FixtureScope {
@@ -52,6 +54,7 @@ pub(crate) struct FixtureAttr {
     name: Option<Ident>,
     teardown: Option<syn::Expr>,
     params: Option<(syn::Visibility, syn::Type, syn::Expr)>,
+    tags: Vec<Ident>,
 }
 
 impl Parse for FixtureAttr {
@@ -61,6 +64,7 @@ impl Parse for FixtureAttr {
         let mut name = None;
         let mut teardown = None;
         let mut params = None;
+        let mut tags = Vec::new();
 
         while !input.is_empty() {
             let ident: Ident = input.parse()?;
@@ -94,6 +98,9 @@ impl Parse for FixtureAttr {
                     let expr = input.parse()?;
                     params = Some((visibility, ty, expr));
                 }
+                "tags" => {
+                    tags = parse_tags(input)?;
+                }
                 _ => {
                     return Err(syn::Error::new_spanned(
                         &ident,
@@ -113,10 +120,56 @@ impl Parse for FixtureAttr {
             name,
             teardown,
             params,
+            tags,
         })
     }
 }
 
+/// The doc comment of an item, from its `#[doc = ".."]` attributes.
+fn doc_comment(attrs: &[syn::Attribute]) -> String {
+    attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                path,
+                value:
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(doc),
+                        ..
+                    }),
+                ..
+            }) if path.is_ident("doc") => Some(doc.value()),
+            _ => None,
+        })
+        .map(|line| line.strip_prefix(' ').map(str::to_owned).unwrap_or(line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The fixture types a fixture depends on, as written in its signature (without `Param`).
+fn dependencies(sig: &syn::Signature) -> Vec<String> {
+    sig.inputs
+        .iter()
+        .filter_map(|input| match input {
+            syn::FnArg::Typed(syn::PatType { ty, .. }) => match ty.as_ref() {
+                syn::Type::Path(syn::TypePath { path, .. }) if path.is_ident("Param") => None,
+                ty => Some(quote! { #ty }.to_string().replace(' ', "")),
+            },
+            syn::FnArg::Receiver(_) => None,
+        })
+        .collect()
+}
+
+/// Is the teardown expression an async closure (`async |v| ..` or `|v| async { .. }`) ?
+fn is_async_closure(expr: &syn::Expr) -> bool {
+    match expr {
+        syn::Expr::Closure(closure) => {
+            closure.asyncness.is_some() || matches!(*closure.body, syn::Expr::Async(_))
+        }
+        _ => false,
+    }
+}
+
 fn get_fixture_type(signature: &syn::Signature) -> Result<(bool, TokenStream), TokenStream> {
     if let ReturnType::Type(_, output_type) = &signature.output {
         match output_type.as_ref() {
@@ -152,8 +205,15 @@ fn get_fixture_type(signature: &syn::Signature) -> Result<(bool, TokenStream), T
 
 pub(crate) fn fixture_impl(args: FixtureAttr, input: ItemFn) -> Result<TokenStream, TokenStream> {
     let ItemFn {
-        sig, block, vis, ..
+        attrs,
+        sig,
+        block,
+        vis,
     } = input;
+    let doc_attrs = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .collect::<Vec<_>>();
 
     let fixture_name = args.name.as_ref().unwrap_or(&sig.ident);
     let def_name = Ident::new(&format!("__{}Def", fixture_name), Span::call_site());
@@ -171,6 +231,7 @@ pub(crate) fn fixture_impl(args: FixtureAttr, input: ItemFn) -> Result<TokenStre
         sub_fixtures,
         sub_fixtures_inputs,
     } = gen_fixture_call(&sig, Some(&mod_name))?;
+    let tags = gen_tags(&args.tags, &sub_fixtures_proxies);
     let sub_proxy_types_tuple = to_tuple(&sub_fixtures_proxies);
     let sub_fixtures_tuple = to_tuple(&sub_fixtures);
     let sub_fixtures_call_args = to_call_args(&sub_fixtures_inputs);
@@ -194,10 +255,43 @@ pub(crate) fn fixture_impl(args: FixtureAttr, input: ItemFn) -> Result<TokenStre
     let sig_inputs = &sig.inputs;
     let builder_output = &sig.output;
 
-    let teardown = args
-        .teardown
-        .map(|expr| quote! { Some(Box::new(#expr)) })
-        .unwrap_or_else(|| quote! { None });
+    let teardown = match args.teardown {
+        Some(expr) if is_async_closure(&expr) => {
+            quote! { Some(::rustest::teardown_fn::<<Self::Fixt as ::rustest::Fixture>::Type, _, _>(|v| ::rustest::block_on((#expr)(v)))) }
+        }
+        // The value type is given for the closure to know its argument type.
+        Some(expr) => {
+            quote! { Some(::rustest::teardown_fn::<<Self::Fixt as ::rustest::Fixture>::Type, _, _>(#expr)) }
+        }
+        None => quote! { None },
+    };
+
+    let asyncness = &sig.asyncness;
+    let setup_call = if asyncness.is_some() {
+        quote! { ::rustest::block_on(user_provided_setup(#(#sub_fixtures_inputs),*)) }
+    } else {
+        quote! { user_provided_setup(#(#sub_fixtures_inputs),*) }
+    };
+
+    // The name is kept as is, fixtures may differ only by case.
+    let register_ident = Ident::new(
+        &format!("__rustest_register_{fixture_name}"),
+        Span::call_site(),
+    );
+    let doc = doc_comment(&attrs);
+    let dependencies = dependencies(&sig);
+    let params_names = match &args.params {
+        Some((_, param_type, expr)) => quote! {
+            Some(|| {
+                (#expr)
+                    .into_iter()
+                    .map(|p| ::rustest::ToParamName::<#param_type>::into_param_name(p).1)
+                    .collect()
+            })
+        },
+        None => quote! { None },
+    };
+    let line = quote_spanned! { sig.ident.span()=> line!() };
 
     let mut phantom_markers = vec![];
     let mut phantom_builders = vec![];
@@ -235,10 +329,10 @@ pub(crate) fn fixture_impl(args: FixtureAttr, input: ItemFn) -> Result<TokenStre
                 use ::rustest::FixtureProxy;
                 #use_param
 
-                fn user_provided_setup #fixture_generics (#sig_inputs) #builder_output #where_clause
+                #asyncness fn user_provided_setup #fixture_generics (#sig_inputs) #builder_output #where_clause
                 #block
 
-                let result = user_provided_setup(#(#sub_fixtures_inputs),*);
+                let result = #setup_call;
                 // Transform the (#fixture_type) into a `Result<#fixture_type, _>` if it is not
                 // already a `Result`.
                 #convert_result
@@ -247,6 +341,14 @@ pub(crate) fn fixture_impl(args: FixtureAttr, input: ItemFn) -> Result<TokenStre
             fn teardown() -> Option<::rustest::TeardownFn<<Self::Fixt as ::rustest::Fixture>::Type>> {
                 #teardown
             }
+
+            fn tags() -> Vec<&'static str> {
+                #tags
+            }
+
+            fn location() -> Option<(&'static str, u32)> {
+                Some((file!(), #line))
+            }
         }
     };
 
@@ -269,6 +371,7 @@ pub(crate) fn fixture_impl(args: FixtureAttr, input: ItemFn) -> Result<TokenStre
         } // end of inner mod
         #def_tokens
 
+        #(#doc_attrs)*
         #vis struct #fixture_name #fixture_generics #where_clause {
             inner: #inner_type,
             #(#phantom_markers),*
@@ -294,14 +397,30 @@ pub(crate) fn fixture_impl(args: FixtureAttr, input: ItemFn) -> Result<TokenStre
                 &self.inner
             }
         }
+
+        ::rustest::ctor! {
+            #[ctor]
+            #[allow(non_snake_case)]
+            fn #register_ident() {
+                ::rustest::register_fixture(::rustest::FixtureInfo {
+                    name: stringify!(#fixture_name),
+                    doc: #doc,
+                    scope: #scope_token,
+                    dependencies: &[#(#dependencies),*],
+                    params: #params_names,
+                    file: file!(),
+                    line: #line,
+                });
+            }
+        }
     })
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{FixtureAttr, FixtureScope};
+    use super::{FixtureAttr, FixtureScope, is_async_closure};
     use quote::quote;
-    use syn::parse2;
+    use syn::{parse_quote, parse2};
 
     #[test]
     fn test_parse_fixture_attr_all_fields() {
@@ -310,7 +429,8 @@ mod tests {
             fallible = true,
             name = my_name,
             teardown = my_teardown_expr,
-            params:my_type = my_params_expr
+            params:my_type = my_params_expr,
+            tags = [network, r#async]
         };
 
         let fixture_attr = parse2::<FixtureAttr>(input).unwrap();
@@ -320,6 +440,7 @@ mod tests {
         assert_eq!(fixture_attr.name.unwrap().to_string(), "my_name");
         assert!(fixture_attr.teardown.is_some());
         assert!(fixture_attr.params.is_some());
+        assert_eq!(fixture_attr.tags.len(), 2);
     }
 
     #[test]
@@ -351,6 +472,16 @@ mod tests {
         assert!(fixture_attr.params.is_none());
     }
 
+    #[test]
+    fn test_is_async_closure() {
+        assert!(is_async_closure(&parse_quote! { async |v| drop(v) }));
+        assert!(is_async_closure(
+            &parse_quote! { |v| async move { drop(v) } }
+        ));
+        assert!(!is_async_closure(&parse_quote! { |v| drop(v) }));
+        assert!(!is_async_closure(&parse_quote! { my_teardown }));
+    }
+
     #[test]
     fn test_parse_fixture_attr_invalid_field() {
         let input = quote! {
@@ -32,16 +32,32 @@ pub fn fixture(args: TokenStream, input: TokenStream) -> TokenStream {
 }
 
 #[proc_macro_attribute]
-pub fn main(_args: TokenStream, _input: TokenStream) -> TokenStream {
+pub fn main(_args: TokenStream, input: TokenStream) -> TokenStream {
+    let mut input = parse_macro_input!(input as ItemFn);
     let test_count = TEST_COUNT.load(Ordering::Relaxed);
+
+    // The body of main is kept in an inner function, given the session if it takes an argument.
+    input.sig.ident = syn::Ident::new("__rustest_main", input.sig.ident.span());
+    let call = if input.sig.inputs.is_empty() {
+        quote! { __rustest_main() }
+    } else {
+        quote! { __rustest_main(&mut session) }
+    };
+
     (quote! {
         static mut TEST_GENERATORS: [Option<::rustest::TestGeneratorFn>; #test_count] = [None; #test_count];
 
         fn main() -> std::process::ExitCode {
+            #input
+
+            let mut session = ::rustest::Session::new();
+            session.set_package(env!("CARGO_PKG_NAME"));
+            #call;
+
             // SAFETY: TEST_CTORS is filled only by functions run from outside of main.
             // So when we are here, no one is modifying (neither read) it.
             let test_registers = unsafe { TEST_GENERATORS.iter().map(|r| r.expect("Slot should be filled")).collect::<Vec<_>>() };
-            ::rustest::run_tests(&test_registers)
+            ::rustest::run_tests(&test_registers, session)
         }
     })
     .into()
@@ -4,12 +4,109 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 use syn::parse::{Parse, ParseStream};
 use syn::{Attribute, ItemFn, LitStr, Meta, MetaNameValue, parse_quote};
 
-use crate::utils::{FixtureInfo, gen_fixture_call, gen_param_fixture, to_call_args};
+use crate::utils::{
+    FixtureInfo, gen_fixture_call, gen_param_fixture, gen_tags, parse_tags, to_call_args,
+};
 
 pub(crate) static TEST_COUNT: AtomicUsize = AtomicUsize::new(0);
 
-fn is_xfail(attrs: &[Attribute]) -> bool {
-    attrs.iter().any(|attr| attr.path().is_ident("xfail"))
+/// The arguments of `xfail`: `xfail(reason = "...", raises = "...", strict = false)`.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct XFailAttr {
+    reason: Option<LitStr>,
+    raises: Option<LitStr>,
+    strict: Option<syn::LitBool>,
+}
+
+impl Parse for XFailAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut xfail = XFailAttr::default();
+        while !input.is_empty() {
+            let ident: Ident = input.parse()?;
+            let _: syn::Token![=] = input.parse()?;
+            match ident.to_string().as_str() {
+                "reason" => xfail.reason = Some(input.parse()?),
+                "raises" => xfail.raises = Some(input.parse()?),
+                "strict" => xfail.strict = Some(input.parse()?),
+                _ => return Err(syn::Error::new_spanned(ident, "unexpected xfail argument")),
+            }
+            if input.peek(syn::Token![,]) {
+                let _: syn::Token![,] = input.parse()?;
+            }
+        }
+        Ok(xfail)
+    }
+}
+
+impl XFailAttr {
+    /// Generate the `::rustest::XFail` described by the attribute.
+    fn gen_xfail(&self) -> TokenStream {
+        let reason = match &self.reason {
+            Some(reason) => quote! { Some(#reason.to_owned()) },
+            None => quote! { None },
+        };
+        let raises = match &self.raises {
+            Some(raises) => quote! { Some(::rustest::Raises::new(#raises)) },
+            None => quote! { None },
+        };
+        let strict = self.strict.as_ref().is_none_or(|strict| strict.value);
+        quote! {
+            ::rustest::XFail {
+                reason: #reason,
+                raises: #raises,
+                strict: #strict,
+            }
+        }
+    }
+}
+
+/// The `#[xfail]` (or `#[xfail(...)]`) attribute of the test, if any.
+fn xfail_attr(attrs: &[Attribute]) -> syn::Result<Option<XFailAttr>> {
+    attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("xfail"))
+        .map(|attr| match &attr.meta {
+            Meta::Path(_) => Ok(XFailAttr::default()),
+            Meta::List(_) => attr.parse_args(),
+            Meta::NameValue(_) => Err(syn::Error::new_spanned(
+                attr,
+                "Invalid format for xfail attr.",
+            )),
+        })
+        .transpose()
+}
+
+/// The `#[should_panic]` attribute of the test, if any, with its expected message (if any).
+///
+/// Both `#[should_panic = "..."]` and `#[should_panic(expected = "...")]` forms are supported.
+fn should_panic_attr(attrs: &[Attribute]) -> syn::Result<Option<Option<LitStr>>> {
+    attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("should_panic"))
+        .map(|attr| match &attr.meta {
+            Meta::Path(_) => Ok(None),
+            Meta::NameValue(MetaNameValue {
+                value:
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(expected),
+                        ..
+                    }),
+                ..
+            }) => Ok(Some(expected.clone())),
+            Meta::List(_) => attr.parse_args_with(|input: ParseStream| {
+                let ident: Ident = input.parse()?;
+                if ident != "expected" {
+                    return Err(syn::Error::new_spanned(ident, "expected `expected`"));
+                }
+                let _: syn::Token![=] = input.parse()?;
+                Ok(Some(input.parse()?))
+            }),
+            _ => Err(syn::Error::new_spanned(
+                attr,
+                "Invalid format for should_panic attr.",
+            )),
+        })
+        .transpose()
 }
 
 fn is_ignored(attrs: &[Attribute]) -> Result<Option<syn::Expr>, TokenStream> {
@@ -35,21 +132,38 @@ fn is_ignored(attrs: &[Attribute]) -> Result<Option<syn::Expr>, TokenStream> {
 
 #[derive(Debug, PartialEq)]
 pub(crate) struct TestAttr {
-    xfail: bool,
+    xfail: Option<XFailAttr>,
     ignore: Option<syn::Expr>,
+    /// The test must panic (with a message containing the given string, if any).
+    should_panic: Option<Option<LitStr>>,
     params: Option<(syn::Visibility, syn::Type, syn::Expr)>,
+    timeout: Option<syn::Expr>,
+    isolated: bool,
+    retries: Option<syn::Expr>,
+    tags: Vec<Ident>,
 }
 
 impl Parse for TestAttr {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        let mut xfail = false;
+        let mut xfail = None;
         let mut ignore = None;
+        let mut should_panic = None;
         let mut params = None;
+        let mut timeout = None;
+        let mut isolated = false;
+        let mut retries = None;
+        let mut tags = Vec::new();
         while !input.is_empty() {
             let ident: Ident = input.parse()?;
             match ident.to_string().as_str() {
                 "xfail" => {
-                    xfail = true;
+                    if input.peek(syn::token::Paren) {
+                        let content;
+                        syn::parenthesized!(content in input);
+                        xfail = Some(content.parse()?);
+                    } else {
+                        xfail = Some(XFailAttr::default());
+                    }
                 }
                 "ignore" => {
                     if input.parse::<syn::Token![=]>().is_ok() {
@@ -59,6 +173,13 @@ impl Parse for TestAttr {
                         ignore = Some(parse_quote! { || true });
                     }
                 }
+                "should_panic" => {
+                    if input.parse::<syn::Token![=]>().is_ok() {
+                        should_panic = Some(Some(input.parse()?));
+                    } else {
+                        should_panic = Some(None);
+                    }
+                }
                 "params" => {
                     let _: syn::Token![:] = input.parse()?;
                     let visibility: syn::Visibility = input.parse()?;
@@ -67,6 +188,20 @@ impl Parse for TestAttr {
                     let expr = input.parse()?;
                     params = Some((visibility, ty, expr));
                 }
+                "isolated" => {
+                    isolated = true;
+                }
+                "retries" => {
+                    let _: syn::Token![=] = input.parse()?;
+                    retries = Some(input.parse()?);
+                }
+                "timeout" => {
+                    let _: syn::Token![=] = input.parse()?;
+                    timeout = Some(input.parse()?);
+                }
+                "tags" => {
+                    tags = parse_tags(input)?;
+                }
 
                 _ => {
                     return Err(input.error("unexpected attribute"));
@@ -79,7 +214,12 @@ impl Parse for TestAttr {
         Ok(TestAttr {
             xfail,
             ignore,
+            should_panic,
             params,
+            timeout,
+            isolated,
+            retries,
+            tags,
         })
     }
 }
@@ -94,7 +234,12 @@ pub(crate) fn test_impl(args: TestAttr, input: ItemFn) -> Result<TokenStream, To
     let TestAttr {
         xfail,
         ignore,
+        should_panic,
         params,
+        timeout,
+        isolated,
+        retries,
+        tags,
     } = args;
 
     let ident = sig.ident.clone();
@@ -104,7 +249,27 @@ pub(crate) fn test_impl(args: TestAttr, input: ItemFn) -> Result<TokenStream, To
     let test_generator_ident = Ident::new(&format!("__{}_register", test_name), Span::call_site());
     let test_register_ident = Ident::new(&format!("__{}_ctor", test_name), Span::call_site());
 
-    let is_xfail = xfail || is_xfail(&attrs);
+    let xfail = match xfail {
+        Some(xfail) => Some(xfail),
+        None => xfail_attr(&attrs).map_err(|e| e.to_compile_error())?,
+    };
+    let is_xfail = xfail.is_some();
+    let with_xfail = xfail.map(|xfail| {
+        let xfail = xfail.gen_xfail();
+        quote! { .with_xfail(#xfail) }
+    });
+    let should_panic = match should_panic {
+        Some(should_panic) => Some(should_panic),
+        None => should_panic_attr(&attrs).map_err(|e| e.to_compile_error())?,
+    };
+    let with_should_panic = should_panic.map(|expected| {
+        let expected = match expected {
+            Some(expected) => quote! { Some(#expected.to_owned()) },
+            None => quote! { None },
+        };
+        quote! { .with_should_panic(#expected) }
+    });
+
     let ignored_fn = match ignore {
         Some(func) => func,
         None => is_ignored(&attrs)?.unwrap_or(parse_quote! {|| false}),
@@ -119,6 +284,33 @@ pub(crate) fn test_impl(args: TestAttr, input: ItemFn) -> Result<TokenStream, To
 
     let param_fixture_def = gen_param_fixture(&params, None);
 
+    let test_call = if sig.asyncness.is_some() {
+        quote! { ::rustest::block_on(#ident::test(#(#sub_fixtures_inputs),*)) }
+    } else {
+        quote! { #ident::test(#(#sub_fixtures_inputs),*) }
+    };
+
+    // Timeout can be given as a number of seconds or as a `Duration`.
+    let timeout = match timeout {
+        None => quote! { None },
+        Some(syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(secs),
+            ..
+        })) => quote! { Some(::std::time::Duration::from_secs(#secs)) },
+        Some(syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Float(secs),
+            ..
+        })) => quote! { Some(::std::time::Duration::from_secs_f64(#secs)) },
+        Some(duration) => quote! { Some(#duration) },
+    };
+
+    let retries = match retries {
+        Some(retries) => quote! { Some(#retries) },
+        None => quote! { None },
+    };
+
+    let tags = gen_tags(&tags, &sub_fixtures_proxies);
+
     let test_idx = TEST_COUNT.fetch_add(1, Ordering::Relaxed);
 
     Ok(quote! {
@@ -133,7 +325,7 @@ pub(crate) fn test_impl(args: TestAttr, input: ItemFn) -> Result<TokenStream, To
 
                     // We have to call build a Test per combination of fixtures.
                     // Lets build a proxy_matrix.
-                    let proxies_matrix = ::rustest::ProxyMatrix::new()#(.feed(#sub_fixtures_proxies::setup(ctx)))*;
+                    let proxies_matrix = ::rustest::ProxyMatrix::new()#(.feed(ctx.get_fixture::<#sub_fixtures_proxies>()))*;
                     let combinations = proxies_matrix.flatten();
 
                     // Append a fixture identifier to test name if we have multiple fixtures instances
@@ -144,19 +336,29 @@ pub(crate) fn test_impl(args: TestAttr, input: ItemFn) -> Result<TokenStream, To
                     };
 
                     let is_ignored = #ignored_fn;
+                    let tags = #tags.into_iter().map(str::to_owned).collect::<Vec<_>>();
 
                     // Lets loop on all the fixture combinations and build a Test for each of them.
                     let tests = combinations.into_iter().map(|c| {
                         use ::rustest::TestName;
                         let name = c.name();
+                        let plan = c.plan();
                         let runner_gen = Box::new(move || {
                             c.call(move |#sub_fixtures_call_args| -> ::rustest::FixtureCreationResult<Box<::rustest::TestRunner>> {
                                 Ok(
-                                    Box::new(|| #ident::test(#(#sub_fixtures_inputs),*).into_error()),
+                                    Box::new(|| #test_call.into_error()),
                                 )
                             })
                         });
-                        ::rustest::Test::new(test_name(name), #is_xfail, is_ignored(), runner_gen)
+                        ::rustest::Test::new(test_name(name.clone()), #is_xfail, is_ignored(), runner_gen)
+                            #with_xfail
+                            #with_should_panic
+                            .with_combination(name)
+                            .with_timeout(#timeout)
+                            .with_isolated(#isolated)
+                            .with_retries(#retries)
+                            .with_tags(tags.clone())
+                            .with_plan(plan)
                     })
                     .collect::<Vec<_>>();
                     tests
@@ -190,9 +392,14 @@ mod tests {
         assert_eq!(
             attr,
             TestAttr {
-                xfail: false,
+                xfail: None,
                 ignore: None,
-                params: None
+                should_panic: None,
+                params: None,
+                timeout: None,
+                isolated: false,
+                retries: None,
+                tags: vec![]
             }
         );
     }
@@ -216,9 +423,14 @@ mod tests {
         assert_eq!(
             attr,
             TestAttr {
-                xfail: true,
+                xfail: Some(XFailAttr::default()),
                 ignore: None,
-                params: None
+                should_panic: None,
+                params: None,
+                timeout: None,
+                isolated: false,
+                retries: None,
+                tags: vec![]
             }
         );
     }
@@ -232,9 +444,14 @@ mod tests {
         assert_eq!(
             attr,
             TestAttr {
-                xfail: false,
+                xfail: None,
                 ignore: Some(parse_quote! {|| true}),
-                params: None
+                should_panic: None,
+                params: None,
+                timeout: None,
+                isolated: false,
+                retries: None,
+                tags: vec![]
             }
         );
     }
@@ -248,9 +465,14 @@ mod tests {
         assert_eq!(
             attr,
             TestAttr {
-                xfail: false,
+                xfail: None,
                 ignore: Some(parse_quote! {|| true}),
-                params: None
+                should_panic: None,
+                params: None,
+                timeout: None,
+                isolated: false,
+                retries: None,
+                tags: vec![]
             }
         );
     }
@@ -273,13 +495,18 @@ mod tests {
         assert_eq!(
             attr,
             TestAttr {
-                xfail: false,
+                xfail: None,
                 ignore: None,
+                should_panic: None,
                 params: Some((
                     Visibility::Inherited,
                     parse_quote! { (u32,u8) },
                     parse_quote! { [(10,5),(42,58)] }
-                ))
+                )),
+                timeout: None,
+                isolated: false,
+                retries: None,
+                tags: vec![]
             }
         );
     }
@@ -294,13 +521,18 @@ mod tests {
         assert_eq!(
             attr,
             TestAttr {
-                xfail: true,
+                xfail: Some(XFailAttr::default()),
                 ignore: None,
+                should_panic: None,
                 params: Some((
                     Visibility::Inherited,
                     parse_quote! { (u32,u8) },
                     parse_quote! { [(10,5),(42,58)] }
-                ))
+                )),
+                timeout: None,
+                isolated: false,
+                retries: None,
+                tags: vec![]
             }
         );
     }
@@ -315,29 +547,142 @@ mod tests {
         assert_eq!(
             attr,
             TestAttr {
-                xfail: true,
+                xfail: Some(XFailAttr::default()),
                 ignore: None,
+                should_panic: None,
                 params: Some((
                     Visibility::Inherited,
                     parse_quote! { (u32,u8) },
                     parse_quote! { [(10,5),(42,58)] }
-                ))
+                )),
+                timeout: None,
+                isolated: false,
+                retries: None,
+                tags: vec![]
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_test_timeout() {
+        let attr: TestAttr = parse_quote! {
+            xfail,
+            timeout = 5
+        };
+
+        assert_eq!(
+            attr,
+            TestAttr {
+                xfail: Some(XFailAttr::default()),
+                ignore: None,
+                should_panic: None,
+                params: None,
+                timeout: Some(parse_quote! { 5 }),
+                isolated: false,
+                retries: None,
+                tags: vec![]
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_test_timeout_duration() {
+        let attr: TestAttr = parse_quote! {
+            timeout = Duration::from_millis(500)
+        };
+
+        assert_eq!(
+            attr,
+            TestAttr {
+                xfail: None,
+                ignore: None,
+                should_panic: None,
+                params: None,
+                timeout: Some(parse_quote! { Duration::from_millis(500) }),
+                isolated: false,
+                retries: None,
+                tags: vec![]
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_test_isolated() {
+        let attr: TestAttr = parse_quote! {
+            isolated,
+            timeout = 5
+        };
+
+        assert_eq!(
+            attr,
+            TestAttr {
+                xfail: None,
+                ignore: None,
+                should_panic: None,
+                params: None,
+                timeout: Some(parse_quote! { 5 }),
+                isolated: true,
+                retries: None,
+                tags: vec![]
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_test_retries() {
+        let attr: TestAttr = parse_quote! {
+            retries = 3
+        };
+
+        assert_eq!(
+            attr,
+            TestAttr {
+                xfail: None,
+                ignore: None,
+                should_panic: None,
+                params: None,
+                timeout: None,
+                isolated: false,
+                retries: Some(parse_quote! { 3 }),
+                tags: vec![]
             }
         );
     }
 
+    #[test]
+    fn test_parse_test_tags() {
+        let attr: TestAttr = parse_quote! {
+            tags = [slow, db], xfail
+        };
+
+        assert_eq!(
+            attr,
+            TestAttr {
+                xfail: Some(XFailAttr::default()),
+                ignore: None,
+                should_panic: None,
+                params: None,
+                timeout: None,
+                isolated: false,
+                retries: None,
+                tags: vec![parse_quote! { slow }, parse_quote! { db }]
+            }
+        );
+        assert!(parse2::<TestAttr>(quote! { tags = slow }).is_err());
+    }
+
     #[test]
     fn test_isxfail_empty() {
         let attr: Vec<Attribute> = vec![];
 
-        assert!(!is_xfail(&attr));
+        assert_eq!(xfail_attr(&attr).unwrap(), None);
     }
 
     #[test]
     fn test_isxfail_xfail() {
         let attr: Vec<Attribute> = parse_quote! {#[xfail]};
 
-        assert!(is_xfail(&attr));
+        assert_eq!(xfail_attr(&attr).unwrap(), Some(XFailAttr::default()));
     }
 
     #[test]
@@ -347,7 +692,86 @@ mod tests {
             #[other]
         };
 
-        assert!(is_xfail(&attr));
+        assert_eq!(xfail_attr(&attr).unwrap(), Some(XFailAttr::default()));
+    }
+
+    #[test]
+    fn test_parse_test_xfail_args() {
+        let attr: TestAttr = parse_quote! {
+            xfail(reason = "bug #12", raises = "overflow", strict = false), isolated
+        };
+
+        assert_eq!(
+            attr,
+            TestAttr {
+                xfail: Some(XFailAttr {
+                    reason: Some(parse_quote! { "bug #12" }),
+                    raises: Some(parse_quote! { "overflow" }),
+                    strict: Some(parse_quote! { false }),
+                }),
+                ignore: None,
+                should_panic: None,
+                params: None,
+                timeout: None,
+                isolated: true,
+                retries: None,
+                tags: vec![]
+            }
+        );
+        assert!(parse2::<TestAttr>(quote! { xfail(strict = "no") }).is_err());
+        assert!(parse2::<TestAttr>(quote! { xfail(because = "bug") }).is_err());
+        // `raises` may be a plain substring, not a valid regex.
+        assert!(parse2::<TestAttr>(quote! { xfail(raises = "values[0") }).is_ok());
+    }
+
+    #[test]
+    fn test_isxfail_xfail_args() {
+        let attr: Vec<Attribute> = parse_quote! {#[xfail(raises = "overflow")]};
+
+        assert_eq!(
+            xfail_attr(&attr).unwrap(),
+            Some(XFailAttr {
+                raises: Some(parse_quote! { "overflow" }),
+                ..Default::default()
+            })
+        );
+
+        let attr: Vec<Attribute> = parse_quote! {#[xfail = "overflow"]};
+        assert!(xfail_attr(&attr).is_err());
+    }
+
+    #[test]
+    fn test_parse_test_should_panic() {
+        let attr: TestAttr = parse_quote! { should_panic };
+        assert_eq!(attr.should_panic, Some(None));
+
+        let attr: TestAttr = parse_quote! { should_panic = "overflow", isolated };
+        assert_eq!(attr.should_panic, Some(Some(parse_quote! { "overflow" })));
+        assert!(attr.isolated);
+    }
+
+    #[test]
+    fn test_should_panic_attr() {
+        let attr: Vec<Attribute> = parse_quote! {#[other]};
+        assert_eq!(should_panic_attr(&attr).unwrap(), None);
+
+        let attr: Vec<Attribute> = parse_quote! {#[should_panic]};
+        assert_eq!(should_panic_attr(&attr).unwrap(), Some(None));
+
+        let attr: Vec<Attribute> = parse_quote! {#[should_panic = "overflow"]};
+        assert_eq!(
+            should_panic_attr(&attr).unwrap(),
+            Some(Some(parse_quote! { "overflow" }))
+        );
+
+        let attr: Vec<Attribute> = parse_quote! {#[should_panic(expected = "overflow")]};
+        assert_eq!(
+            should_panic_attr(&attr).unwrap(),
+            Some(Some(parse_quote! { "overflow" }))
+        );
+
+        let attr: Vec<Attribute> = parse_quote! {#[should_panic(message = "overflow")]};
+        assert!(should_panic_attr(&attr).is_err());
     }
 
     #[test]
@@ -356,7 +780,7 @@ mod tests {
             #[other]
         };
 
-        assert!(!is_xfail(&attr));
+        assert_eq!(xfail_attr(&attr).unwrap(), None);
     }
 
     #[test]
@@ -416,9 +840,14 @@ mod tests {
         };
 
         let args = TestAttr {
-            xfail: false,
+            xfail: None,
             ignore: None,
+            should_panic: None,
             params: None,
+            timeout: None,
+            isolated: false,
+            retries: None,
+            tags: vec![],
         };
 
         let result = test_impl(args, input);
@@ -1,6 +1,9 @@
 use proc_macro2::{Span, TokenStream};
 use quote::quote;
-use syn::{Expr, FnArg, Ident, PatType, PathArguments, Signature, Type, TypePath, Visibility};
+use syn::{
+    Expr, FnArg, Ident, LitStr, PatType, PathArguments, Signature, Type, TypePath, Visibility,
+    ext::IdentExt, parse::ParseStream, punctuated::Punctuated,
+};
 
 pub fn to_tuple(input: &[TokenStream]) -> TokenStream {
     if input.is_empty() {
@@ -17,6 +20,32 @@ pub fn to_call_args(input: &[TokenStream]) -> TokenStream {
     quote! { ::rustest::CallArgs(#tuple) }
 }
 
+/// Parse the `= [tag, ...]` part of a `tags` attribute argument.
+pub(crate) fn parse_tags(input: ParseStream) -> syn::Result<Vec<Ident>> {
+    let _: syn::Token![=] = input.parse()?;
+    let content;
+    syn::bracketed!(content in input);
+    let tags =
+        Punctuated::<Ident, syn::Token![,]>::parse_terminated_with(&content, Ident::parse_any)?;
+    Ok(tags.into_iter().collect())
+}
+
+/// Generate an expression collecting the tags (as `Vec<&'static str>`) of an item using
+/// `sub_fixtures_proxies`.
+pub(crate) fn gen_tags(tags: &[Ident], sub_fixtures_proxies: &[TokenStream]) -> TokenStream {
+    let tags = tags
+        .iter()
+        .map(|tag| LitStr::new(&tag.unraw().to_string(), tag.span()));
+    quote! {
+        {
+            #[allow(unused_mut)]
+            let mut tags: Vec<&'static str> = vec![#(#tags),*];
+            #(tags.extend(<#sub_fixtures_proxies as ::rustest::FixtureProxy>::tags());)*
+            tags
+        }
+    }
+}
+
 pub struct FixtureInfo {
     pub sub_fixtures_proxies: Vec<TokenStream>,
     pub sub_fixtures: Vec<TokenStream>,
@@ -123,7 +152,7 @@ pub(crate) fn gen_param_fixture(
                 const SCOPE : ::rustest::FixtureScope = ::rustest::FixtureScope::Test;
 
                 fn setup(ctx: &mut ::rustest::TestContext) -> Vec<Self> {
-                    #expr.into_iter().map(Self::new).collect()
+                    ctx.params(|| #expr.into_iter().map(Self::new).collect())
                 }
 
                 fn build(self) -> ::rustest::FixtureCreationResult<Self::Fixt> {
@@ -0,0 +1,66 @@
+use rustest::{test, *};
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// A future pending once, so async code really has to be polled again.
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+async fn double(value: u32) -> u32 {
+    YieldNow(false).await;
+    value * 2
+}
+
+#[fixture(scope=global, teardown=async |v| { YieldNow(false).await; println!("Async teardown of {v}") })]
+async fn GlobalNumber() -> u32 {
+    double(21).await
+}
+
+#[fixture(teardown=|v| async move { YieldNow(false).await; println!("Async block teardown of {v}") })]
+async fn Number(global: GlobalNumber) -> u32 {
+    double(*global).await
+}
+
+#[fixture]
+async fn FallibleNumber() -> std::result::Result<u32, std::num::ParseIntError> {
+    YieldNow(false).await;
+    "5".parse()
+}
+
+#[test]
+async fn test_async(number: Number) {
+    assert_eq!(*number, 84);
+    assert_eq!(double(*number).await, 168);
+}
+
+#[test]
+async fn test_async_result(number: FallibleNumber) -> Result {
+    YieldNow(false).await;
+    assert_eq!(*number, 5);
+    Ok(())
+}
+
+#[test(xfail)]
+async fn test_async_failing() {
+    YieldNow(false).await;
+    panic!("Async failure");
+}
+
+#[main]
+fn main() {}
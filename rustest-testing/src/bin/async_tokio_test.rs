@@ -0,0 +1,48 @@
+use rustest::{test, *};
+use std::{rc::Rc, time::Duration};
+
+async fn double(value: u32) -> u32 {
+    tokio::time::sleep(Duration::from_millis(1)).await;
+    value * 2
+}
+
+#[fixture(scope=global, teardown=async |v| { tokio::time::sleep(Duration::from_millis(1)).await; println!("Async teardown of {v}") })]
+async fn GlobalNumber() -> u32 {
+    tokio::spawn(double(21)).await.unwrap()
+}
+
+#[fixture]
+async fn Number(global: GlobalNumber) -> u32 {
+    double(*global).await
+}
+
+#[test]
+async fn test_tokio(number: Number) {
+    assert_eq!(*number, 84);
+    assert_eq!(tokio::spawn(double(*number)).await.unwrap(), 168);
+}
+
+// Futures do not have to be `Send`.
+#[test]
+async fn test_not_send() {
+    let value = Rc::new(21);
+    assert_eq!(double(*value).await, 42);
+}
+
+#[test]
+fn test_block_on_in_current_thread_runtime() {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let value = runtime.block_on(async {
+        block_on(async {
+            let value = Rc::new(21);
+            tokio::spawn(double(*value)).await.unwrap()
+        })
+    });
+    assert_eq!(value, 42);
+}
+
+#[main]
+fn main() {}
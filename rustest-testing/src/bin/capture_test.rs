@@ -0,0 +1,56 @@
+use rustest::{test, *};
+
+#[fixture(teardown=|v| println!("Teardown of {v}"))]
+fn Number() -> u32 {
+    println!("Setup of Number");
+    5
+}
+
+#[test]
+fn test_passing(number: Number) {
+    println!("Passing with {}", *number);
+}
+
+#[test]
+fn test_failing(number: Number) {
+    println!("Failing with {}", *number);
+    eprintln!("On stderr");
+    assert_eq!(*number, 6);
+}
+
+#[test]
+fn test_parallel_first() {
+    println!("First starts");
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    println!("First ends");
+    panic!("First fails");
+}
+
+#[test]
+fn test_parallel_second() {
+    println!("Second starts");
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    println!("Second ends");
+    panic!("Second fails");
+}
+
+#[test(isolated)]
+fn test_isolated_failing() {
+    println!("Isolated output");
+    std::process::exit(3);
+}
+
+#[test(timeout = 0.1)]
+fn test_timeout_first() {
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    println!("Timed out test still running");
+}
+
+#[test]
+fn test_timeout_next() {
+    std::thread::sleep(std::time::Duration::from_millis(300));
+    panic!("Next fails");
+}
+
+#[main]
+fn main() {}
@@ -0,0 +1,29 @@
+use std::{thread::sleep, time::Duration};
+
+use rustest::{test, *};
+
+#[fixture(scope=global, teardown=|_| sleep(Duration::from_millis(100)))]
+fn Server() -> u32 {
+    sleep(Duration::from_millis(200));
+    8080
+}
+
+#[fixture(teardown=|_| sleep(Duration::from_millis(100)))]
+fn Client(server: Server) -> u32 {
+    sleep(Duration::from_millis(100));
+    *server
+}
+
+#[test]
+fn test_slow(client: Client) {
+    sleep(Duration::from_millis(300));
+    assert_eq!(*client, 8080);
+}
+
+#[test]
+fn test_fast(server: Server) {
+    assert_eq!(*server, 8080);
+}
+
+#[main]
+fn main() {}
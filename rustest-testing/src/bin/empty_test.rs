@@ -0,0 +1,4 @@
+use rustest::main;
+
+#[main]
+fn main() {}
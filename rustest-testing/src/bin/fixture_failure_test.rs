@@ -0,0 +1,73 @@
+use rustest::{test, *};
+
+#[fixture(scope=global)]
+fn Server() -> u16 {
+    println!("starting Server");
+    panic!("Cannot bind port 8080");
+}
+
+#[fixture]
+fn Client(server: Server) -> String {
+    format!("localhost:{}", *server)
+}
+
+#[fixture(scope=global)]
+fn Database() -> std::result::Result<String, std::io::Error> {
+    println!("connecting Database");
+    Err(std::io::Error::other("Connection refused"))
+}
+
+#[fixture(params:u32 = [1, 3])]
+fn ParamNumber(Param(n): Param) -> std::result::Result<u32, std::io::Error> {
+    if n % 2 == 1 && n > 1 {
+        Err(std::io::Error::other(format!("invalid number {n}")))
+    } else {
+        Ok(n)
+    }
+}
+
+#[fixture]
+fn Double<Source>(number: Source) -> u32
+where
+    Source: SubFixture<Type = u32>,
+{
+    *number * 2
+}
+
+#[test]
+fn test_double(double: Double<ParamNumber>) {
+    assert!(*double > 0);
+}
+
+#[test]
+fn test_server(server: Server) {
+    assert_eq!(*server, 8080);
+}
+
+#[test]
+fn test_server_again(server: Server) {
+    assert_eq!(*server, 8080);
+}
+
+#[test]
+fn test_client(client: Client) {
+    assert_eq!(*client, "localhost:8080");
+}
+
+#[test]
+fn test_database(database: Database) {
+    assert!(!database.is_empty());
+}
+
+#[test]
+fn test_database_again(database: Database) {
+    assert!(!database.is_empty());
+}
+
+#[test]
+fn test_assertion() {
+    assert_eq!(1 + 1, 3);
+}
+
+#[main]
+fn main() {}
@@ -0,0 +1,38 @@
+use rustest::{test, *};
+
+/// The port the server listens on.
+///
+/// Each test gets a new port.
+#[fixture(params:u16=[8080, 8081])]
+fn Port(Param(port): Param) -> u16 {
+    port
+}
+
+/// A server shared by all the tests.
+#[fixture(scope=global)]
+fn Server(port: Port) -> String {
+    format!("localhost:{}", *port)
+}
+
+#[fixture(scope = test)]
+fn Unused() -> u32 {
+    42
+}
+
+mod other {
+    use super::*;
+
+    /// Another server, with the same name.
+    #[fixture]
+    fn Server() -> String {
+        "localhost:9090".to_owned()
+    }
+}
+
+#[test]
+fn test_server(server: Server) {
+    assert!(server.starts_with("localhost:"));
+}
+
+#[main]
+fn main() {}
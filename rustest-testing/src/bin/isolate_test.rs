@@ -0,0 +1,41 @@
+use rustest::{test, *};
+
+#[fixture]
+fn Number() -> u32 {
+    5
+}
+
+#[test]
+fn test_number(number: Number) {
+    println!("OUTPUT test_number");
+    assert_eq!(*number, 5);
+}
+
+#[test]
+fn test_failing(number: Number) {
+    assert_eq!(*number, 6);
+}
+
+#[test(isolated)]
+fn test_abort() {
+    std::process::abort();
+}
+
+#[test(isolated)]
+fn test_segfault() {
+    // SAFETY: It is not. We want to crash.
+    unsafe { std::ptr::null_mut::<u32>().write_volatile(42) };
+}
+
+#[test(isolated)]
+fn test_exit() {
+    std::process::exit(3);
+}
+
+#[test(isolated, xfail)]
+fn test_isolated_xfail() {
+    panic!("Expected failure");
+}
+
+#[main]
+fn main() {}
@@ -0,0 +1,101 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use rustest::{test, *};
+
+#[fixture(scope=global, teardown=|_| ())]
+fn Server() -> u32 {
+    8080
+}
+
+#[fixture(teardown=|_| ())]
+fn Client(server: Server) -> u32 {
+    *server
+}
+
+#[fixture]
+fn Broken() -> std::io::Result<u32> {
+    Err(std::io::Error::other("Cannot build Broken"))
+}
+
+#[test]
+fn test_client(client: Client) {
+    assert_eq!(*client, 8080);
+}
+
+#[test]
+fn test_broken(broken: Broken) {
+    assert_eq!(*broken, 0);
+}
+
+#[test]
+fn test_removed_by_plugin() {
+    panic!("This test should have been removed");
+}
+
+/// Remove the tests named `*_removed_by_plugin` and record the hooks called.
+#[derive(Default)]
+struct Recorder {
+    events: Arc<Mutex<Vec<String>>>,
+}
+
+impl Recorder {
+    fn record(&self, event: String) {
+        self.events.lock().unwrap().push(event);
+    }
+}
+
+impl Plugin for Recorder {
+    fn on_collect(&self, tests: &mut Vec<Test>) {
+        tests.retain(|test| !test.name().ends_with("_removed_by_plugin"));
+        self.record(format!("collect {}", tests.len()));
+    }
+
+    fn on_test_start(&self, test: &Test) {
+        self.record(format!("start {}", test.name()));
+    }
+
+    fn on_test_end(&self, report: &TestReport) {
+        let outcome = match report.outcome {
+            Outcome::Passed => "passed",
+            Outcome::Error(_) => "error",
+            _ => "failed",
+        };
+        self.record(format!("end {} {outcome}", report.name));
+    }
+
+    fn on_fixture_setup(
+        &self,
+        fixture: &str,
+        scope: FixtureScope,
+        _duration: Duration,
+        error: Option<&FixtureCreationError>,
+    ) {
+        let status = if error.is_some() { "failed" } else { "ok" };
+        self.record(format!("setup {fixture} {scope:?} {status}"));
+    }
+
+    fn on_fixture_teardown(
+        &self,
+        fixture: &str,
+        scope: FixtureScope,
+        _duration: Duration,
+        error: Option<&str>,
+    ) {
+        let status = if error.is_some() { "failed" } else { "ok" };
+        self.record(format!("teardown {fixture} {scope:?} {status}"));
+    }
+}
+
+#[main]
+fn main(session: &mut Session) {
+    let recorder = Recorder::default();
+    let events = Arc::clone(&recorder.events);
+    session.plugin(recorder).on_finish(move |_| {
+        for event in events.lock().unwrap().iter() {
+            println!("plugin: {event}");
+        }
+    });
+}
@@ -0,0 +1,53 @@
+use rustest::{test, *};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static BUILDS: AtomicUsize = AtomicUsize::new(0);
+static GLOBAL_BUILDS: AtomicUsize = AtomicUsize::new(0);
+static CALLS: AtomicUsize = AtomicUsize::new(0);
+static PARAMS_COLLECTED: AtomicUsize = AtomicUsize::new(0);
+static ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+
+/// Params which are different each time they are collected.
+fn params() -> Vec<usize> {
+    vec![PARAMS_COLLECTED.fetch_add(1, Ordering::SeqCst)]
+}
+
+#[fixture(scope=test)]
+fn Counter() -> usize {
+    BUILDS.fetch_add(1, Ordering::SeqCst) + 1
+}
+
+#[fixture(scope=global)]
+fn GlobalCounter() -> usize {
+    GLOBAL_BUILDS.fetch_add(1, Ordering::SeqCst) + 1
+}
+
+// Fixtures are built again for each attempt, so the test passes on its second attempt.
+#[test(retries = 2)]
+fn test_flaky(counter: Counter, global: GlobalCounter) {
+    assert_eq!(*global, 1);
+    assert!(*counter >= 2, "Counter is {}", *counter);
+}
+
+#[test(retries = 2)]
+fn test_always_failing() {
+    panic!("Always failing");
+}
+
+#[test]
+fn test_fail_once() {
+    let calls = CALLS.fetch_add(1, Ordering::SeqCst);
+    assert!(calls > 0, "First call fails");
+}
+
+// Retries are built when the test fails, with the params collected at first.
+#[test(params:usize=params(), retries = 1)]
+fn test_retry_params(Param(param): Param) {
+    assert_eq!(param, 0);
+    assert_eq!(PARAMS_COLLECTED.load(Ordering::SeqCst), 1);
+    let attempt = ATTEMPTS.fetch_add(1, Ordering::SeqCst);
+    assert!(attempt > 0, "First attempt fails");
+}
+
+#[main]
+fn main() {}
@@ -0,0 +1,37 @@
+use rustest::{test, *};
+
+#[fixture(scope=global, teardown=|_| println!("Server torn down"))]
+fn Server() -> u32 {
+    8080
+}
+
+#[test]
+fn test_server(server: Server) {
+    assert_eq!(*server, 8080);
+}
+
+#[test]
+fn test_fail() {
+    panic!("Failing test");
+}
+
+#[main]
+fn main(session: &mut Session) {
+    println!("main run");
+    session
+        .on_start(|| {
+            if std::env::var_os("MISSING_PREREQUISITE").is_some() {
+                return Err("prerequisite is missing".into());
+            }
+            println!("session started");
+            Ok(())
+        })
+        .on_finish(|conclusion| {
+            println!(
+                "session finished: {} passed, {} failed, {} global fixture(s)",
+                conclusion.num_passed,
+                conclusion.num_failed,
+                conclusion.global_fixtures.len()
+            );
+        });
+}
@@ -0,0 +1,35 @@
+use rustest::{test, *};
+
+// Setup plan must not build any fixture.
+#[fixture(scope=global)]
+fn Database() -> String {
+    panic!("Database must not be set up")
+}
+
+#[fixture]
+fn Connection(db: Database) -> String {
+    format!("connection to {}", *db)
+}
+
+#[fixture(params:u32=[1, 2, 3])]
+fn Mode(Param(mode): Param) -> u32 {
+    mode
+}
+
+#[fixture(params:u32=[10, 20])]
+fn Size(Param(size): Param) -> u32 {
+    size
+}
+
+#[test]
+fn test_connection(connection: Connection) {
+    assert_eq!(*connection, "connection to db");
+}
+
+#[test]
+fn test_matrix(db: Database, mode: Mode, size: Size) {
+    assert!(!db.is_empty() && *mode * *size > 0);
+}
+
+#[main]
+fn main() {}
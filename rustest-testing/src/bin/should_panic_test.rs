@@ -0,0 +1,48 @@
+use rustest::{test, *};
+
+#[fixture]
+fn Divisor() -> u32 {
+    0
+}
+
+#[test]
+#[should_panic]
+fn test_panics() {
+    panic!("Boom");
+}
+
+#[test]
+#[should_panic(expected = "overflow")]
+fn test_expected() {
+    u8::MAX
+        .checked_add(1)
+        .expect("attempt to add with overflow");
+}
+
+#[test]
+#[should_panic = "overflow"]
+fn test_expected_name_value() {
+    u8::MAX
+        .checked_add(1)
+        .expect("attempt to add with overflow");
+}
+
+#[test(should_panic = "divide by zero")]
+fn test_expected_arg(divisor: Divisor) {
+    let _ = 1u32
+        .checked_div(*divisor)
+        .expect("attempt to divide by zero");
+}
+
+#[test]
+#[should_panic(expected = "overflow")]
+fn test_wrong_panic() {
+    panic!("division by zero");
+}
+
+#[test]
+#[should_panic]
+fn test_no_panic() {}
+
+#[main]
+fn main() {}
@@ -0,0 +1,40 @@
+use std::{thread::sleep, time::Duration};
+
+use rustest::{test, *};
+
+#[fixture(scope=global, teardown=|_| println!("Teardown of server"))]
+fn Server() -> u32 {
+    println!("Setup of server");
+    8080
+}
+
+#[test]
+fn test_1_server(server: Server) {
+    assert_eq!(*server, 8080);
+}
+
+#[test]
+fn test_2_slow(server: Server) {
+    println!("Slow test started");
+    sleep(Duration::from_secs(1));
+    assert_eq!(*server, 8080);
+}
+
+#[test]
+fn test_3_not_run(server: Server) {
+    assert_eq!(*server, 8080);
+}
+
+#[test]
+fn test_4_hanging() {
+    println!("Hanging test started");
+    sleep(Duration::from_secs(60));
+}
+
+#[test]
+fn test_5_queued(server: Server) {
+    assert_eq!(*server, 8080);
+}
+
+#[main]
+fn main() {}
@@ -0,0 +1,48 @@
+use rustest::{test, *};
+
+#[fixture(scope=global)]
+fn Tool() -> &'static str {
+    println!("building Tool");
+    skip!("tool {} is not available", "frobnicate");
+}
+
+#[fixture]
+fn ToolVersion(tool: Tool) -> String {
+    format!("{} 1.0", *tool)
+}
+
+#[test]
+fn test_passes() {}
+
+#[test]
+fn test_skip_in_body() {
+    skip!("not today");
+}
+
+#[test]
+fn test_skip_without_reason() {
+    skip!();
+}
+
+#[test]
+fn test_tool(tool: Tool) {
+    assert_eq!(*tool, "frobnicate");
+}
+
+#[test]
+fn test_tool_again(tool: Tool) {
+    assert_eq!(*tool, "frobnicate");
+}
+
+#[test]
+fn test_tool_version(version: ToolVersion) {
+    assert_eq!(*version, "frobnicate 1.0");
+}
+
+#[test(xfail)]
+fn test_skip_xfail() {
+    skip!("skipped before failing");
+}
+
+#[main]
+fn main() {}
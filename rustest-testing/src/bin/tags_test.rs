@@ -0,0 +1,42 @@
+use rustest::{test, *};
+use rustest_fixtures::Global;
+
+#[fixture(tags = [network])]
+fn Server() -> u16 {
+    8080
+}
+
+// Tags are inherited through the fixture dependencies.
+#[fixture(scope = test, tags = [db])]
+fn Database(server: Server) -> String {
+    format!("db on {}", *server)
+}
+
+#[fixture(params:u32=[1, 2])]
+fn Number(p: Param) -> u32 {
+    *p
+}
+
+#[test(tags = [slow])]
+fn test_slow() {}
+
+#[test(tags = [slow])]
+fn test_slow_db(db: Database) {
+    assert_eq!(*db, "db on 8080");
+}
+
+#[test]
+fn test_db(db: Global<Database>) {
+    assert_eq!(db.as_str(), "db on 8080");
+}
+
+#[test(tags = [fast])]
+fn test_number(number: Number) {
+    assert!(*number > 0);
+}
+
+#[test]
+fn test_untagged() {}
+
+#[main]
+fn main() {}
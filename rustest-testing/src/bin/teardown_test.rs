@@ -0,0 +1,57 @@
+use rustest::{test, *};
+
+#[fixture(teardown = |pid| Err(std::io::Error::other(format!("cannot kill process {pid}"))))]
+fn Process() -> u32 {
+    42
+}
+
+#[fixture(teardown = |dir| std::fs::remove_dir(*dir).expect("Cannot remove directory"))]
+fn TempDir() -> &'static str {
+    "/tmp/rustest-teardown-test-missing-dir"
+}
+
+#[fixture(teardown = |v| println!("Closing {v}"))]
+fn Connection() -> &'static str {
+    "connection"
+}
+
+#[fixture(scope = global, teardown = async |port| -> std::io::Result<()> {
+    Err(std::io::Error::other(format!("port {port} is still in use")))
+})]
+fn Server() -> u16 {
+    8080
+}
+
+#[test]
+fn test_process(pid: Process) {
+    assert_eq!(*pid, 42);
+}
+
+#[test]
+fn test_process_failing(pid: Process) {
+    assert_eq!(*pid, 43, "Wrong process");
+}
+
+#[test]
+fn test_tempdir(dir: TempDir) {
+    assert!(dir.starts_with("/tmp"));
+}
+
+#[test]
+fn test_tempdir_failing(dir: TempDir) {
+    // The teardown panics while the test is unwinding.
+    assert!(dir.starts_with("/var"), "Wrong directory");
+}
+
+#[test]
+fn test_connection(connection: Connection) {
+    assert_eq!(*connection, "connection");
+}
+
+#[test]
+fn test_server(server: Server) {
+    assert_eq!(*server, 8080);
+}
+
+#[main]
+fn main() {}
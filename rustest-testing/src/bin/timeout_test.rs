@@ -0,0 +1,55 @@
+use rustest::{test, *};
+use std::time::Duration;
+
+#[fixture]
+fn SlowFixture() -> u32 {
+    std::thread::sleep(Duration::from_secs(60));
+    5
+}
+
+#[fixture]
+fn Number() -> u32 {
+    5
+}
+
+#[test(timeout = 5)]
+fn test_fast(number: Number) {
+    assert_eq!(*number, 5);
+}
+
+#[test(timeout = Duration::from_millis(300))]
+fn test_hung_body(number: Number) {
+    std::thread::sleep(Duration::from_secs(60));
+    assert_eq!(*number, 5);
+}
+
+#[test(timeout = 0.3)]
+fn test_hung_fixture(number: SlowFixture) {
+    assert_eq!(*number, 5);
+}
+
+#[test(isolated, timeout = 0.3)]
+fn test_hung_isolated() {
+    // Keeps our stdout and stderr open until killed.
+    let _child = std::process::Command::new("sleep").arg("60").spawn();
+    std::thread::sleep(Duration::from_secs(60));
+}
+
+#[test(isolated, timeout = 0.3)]
+fn test_hung_isolated_fixture(number: SlowFixture) {
+    assert_eq!(*number, 5);
+}
+
+#[test(isolated, timeout = 2)]
+fn test_background_process() {
+    // Keeps our stdout and stderr open after we exit.
+    let _child = std::process::Command::new("sleep").arg("5").spawn();
+}
+
+#[test]
+fn test_slow() {
+    std::thread::sleep(Duration::from_millis(200));
+}
+
+#[main]
+fn main() {}
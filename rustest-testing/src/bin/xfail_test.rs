@@ -0,0 +1,42 @@
+use rustest::{test, *};
+
+#[test]
+#[xfail]
+fn test_xfail() {
+    panic!("Boom");
+}
+
+#[test(xfail(raises = "overflow"))]
+fn test_xfail_raises() {
+    panic!("attempt to add with overflow");
+}
+
+#[test(xfail(raises = r"code \d+"))]
+fn test_xfail_raises_regex() {
+    panic!("failed with code 42");
+}
+
+#[test(xfail(raises = "values[0]"))]
+fn test_xfail_raises_substring() {
+    panic!("values[0] is out of range");
+}
+
+#[test(xfail(raises = "values[0"))]
+fn test_xfail_raises_not_regex() {
+    panic!("values[0 is not closed");
+}
+
+#[test(xfail(raises = "overflow"))]
+fn test_xfail_wrong_failure() {
+    panic!("division by zero");
+}
+
+#[test(xfail(reason = "bug #12", strict = false))]
+fn test_xpass() {}
+
+#[test]
+#[xfail(reason = "bug #13")]
+fn test_xpass_strict() {}
+
+#[main]
+fn main() {}
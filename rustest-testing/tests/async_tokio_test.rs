@@ -0,0 +1,30 @@
+fn run(options: &[&str]) -> std::io::Result<std::process::Output> {
+    let exec = env!("CARGO_BIN_EXE_async_tokio_test");
+    let mut command = std::process::Command::new(exec);
+    command.env("NO_COLOR", "1");
+    command.args(options);
+    command.output()
+}
+
+fn lines(output: &std::process::Output) -> Vec<String> {
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|l| l.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect()
+}
+
+#[test]
+fn test_async_tokio() {
+    let output = run(&["--test-threads", "1", "--show-output"]).unwrap();
+    assert!(output.status.success());
+    let lines = lines(&output);
+    for expected in [
+        "test result: ok. 3 passed; 0 failed;",
+        "Async teardown of 42",
+    ] {
+        assert!(
+            lines.iter().any(|l| l.contains(expected)),
+            "{expected}\n{lines:#?}"
+        );
+    }
+}
@@ -0,0 +1,146 @@
+fn run(options: &[&str]) -> std::io::Result<std::process::Output> {
+    let exec = env!("CARGO_BIN_EXE_capture_test");
+    let mut command = std::process::Command::new(exec);
+    command.env("NO_COLOR", "1");
+    command.args(["--test-threads", "1"]);
+    command.args(options);
+    command.output()
+}
+
+fn lines(output: &std::process::Output) -> Vec<String> {
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|l| l.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect()
+}
+
+fn check_contains(lines: &[String], expected: &[&str]) {
+    for expected in expected {
+        assert!(
+            lines.iter().any(|l| l == expected),
+            "{expected}\n{lines:#?}"
+        );
+    }
+}
+
+#[test]
+fn test_capture() {
+    let output = run(&["--skip", "test_timeout"]).unwrap();
+    assert_eq!(output.status.code(), Some(101));
+    let lines = lines(&output);
+    check_contains(
+        &lines,
+        &[
+            // Output is not mixed with the test lines.
+            "test test_passing ... ok",
+            "test test_failing ... FAILED",
+            "---- test_failing stdout ----",
+            "Setup of Number",
+            "Failing with 5",
+            "On stderr",
+            "Teardown of 5",
+            "---- test_isolated_failing stdout ----",
+            "Isolated output",
+        ],
+    );
+    // Output of passing tests is not shown.
+    assert!(!lines.iter().any(|l| l.contains("Passing")), "{lines:#?}");
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn test_show_output() {
+    let output = run(&["--show-output"]).unwrap();
+    let lines = lines(&output);
+    check_contains(
+        &lines,
+        &[
+            "successes:",
+            "---- test_passing stdout ----",
+            "Passing with 5",
+            "---- test_failing stdout ----",
+        ],
+    );
+}
+
+#[test]
+fn test_nocapture() {
+    let output = run(&["--nocapture"]).unwrap();
+    let lines = lines(&output);
+    check_contains(&lines, &["test test_passing ... Setup of Number"]);
+    assert!(
+        !lines.iter().any(|l| l.ends_with("stdout ----")),
+        "{lines:#?}"
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("On stderr"), "{stderr}");
+}
+
+#[test]
+fn test_json_output() {
+    let output = run(&["--format", "json", "test_passing"]).unwrap();
+    let lines = lines(&output);
+    assert!(
+        lines
+            .iter()
+            .any(|l| l.contains(r#""output":"Setup of Number\nPassing with 5\nTeardown of 5\n""#)),
+        "{lines:#?}"
+    );
+}
+
+#[test]
+fn test_parallel_capture() {
+    let exec = env!("CARGO_BIN_EXE_capture_test");
+    let output = std::process::Command::new(exec)
+        .env("NO_COLOR", "1")
+        .args(["--test-threads", "2", "test_parallel"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(101));
+    let lines = lines(&output);
+    // The output of each test, up to the next section.
+    let section = |name: &str| {
+        let header = format!("---- {name} stdout ----");
+        lines
+            .iter()
+            .skip_while(|l| **l != header)
+            .skip(1)
+            .take_while(|l| !l.starts_with("----") && !l.is_empty())
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+    let first = section("test_parallel_first");
+    assert!(first.iter().any(|l| l == "First ends"), "{lines:#?}");
+    assert!(!first.iter().any(|l| l.contains("Second")), "{lines:#?}");
+    let second = section("test_parallel_second");
+    assert!(second.iter().any(|l| l == "Second ends"), "{lines:#?}");
+    assert!(!second.iter().any(|l| l.contains("First")), "{lines:#?}");
+}
+
+#[test]
+fn test_timeout_stops_capture() {
+    let output = run(&["test_timeout"]).unwrap();
+    assert_eq!(output.status.code(), Some(101));
+    let lines = lines(&output);
+    check_contains(&lines, &["test test_timeout_first ... TIMEOUT"]);
+    // The output of the test still running is not captured as the output of the next test.
+    assert!(
+        lines
+            .iter()
+            .any(|l| l.ends_with("Timed out test still running")),
+        "{lines:#?}"
+    );
+    assert!(
+        !lines
+            .iter()
+            .any(|l| l == "---- test_timeout_next stdout ----"),
+        "{lines:#?}"
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains(
+            "test test_timeout_first has timed out and is still running, output is not captured anymore"
+        ),
+        "{stderr}"
+    );
+}
@@ -0,0 +1,77 @@
+fn run(options: &[&str]) -> std::io::Result<std::process::Output> {
+    let exec = env!("CARGO_BIN_EXE_durations_test");
+    let mut command = std::process::Command::new(exec);
+    command.env("NO_COLOR", "1");
+    command.args(["--test-threads", "1"]);
+    command.args(options);
+    command.output()
+}
+
+fn lines(output: &std::process::Output) -> Vec<String> {
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|l| l.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect()
+}
+
+/// Parse the seconds of a line like "0.701s (0.100s / 0.300s / 0.100s) test_slow".
+fn seconds(line: &str) -> Vec<f64> {
+    line.split(|c: char| c.is_whitespace() || c == '(' || c == ')')
+        .filter_map(|s| s.strip_suffix('s')?.parse().ok())
+        .collect()
+}
+
+#[test]
+fn test_durations() {
+    let output = run(&["--durations", "1"]).unwrap();
+    assert!(output.status.success());
+    let lines = lines(&output);
+    let idx = lines
+        .iter()
+        .position(|l| l == "slowest 1 durations (setup / call / teardown):")
+        .unwrap_or_else(|| panic!("{lines:#?}"));
+
+    // Only the slowest test is reported.
+    let slowest = &lines[idx + 1];
+    assert!(slowest.ends_with(" test_slow"), "{lines:#?}");
+    assert!(!lines.iter().any(|l| l.ends_with(") test_fast")));
+    let [total, setup, call, teardown] = seconds(slowest)[..] else {
+        panic!("{slowest}");
+    };
+    // Setup of the global fixture is not part of the test setup.
+    assert!((0.1..0.2).contains(&setup), "{slowest}");
+    assert!((0.3..0.4).contains(&call), "{slowest}");
+    assert!((0.1..0.2).contains(&teardown), "{slowest}");
+    assert!(total >= setup + call + teardown, "{slowest}");
+
+    let idx = lines
+        .iter()
+        .position(|l| l == "global fixtures (setup / teardown):")
+        .unwrap_or_else(|| panic!("{lines:#?}"));
+    let server = &lines[idx + 1];
+    assert!(server.ends_with(" Server"), "{lines:#?}");
+    let [setup, teardown] = seconds(server)[..] else {
+        panic!("{server}");
+    };
+    assert!((0.2..0.3).contains(&setup), "{server}");
+    assert!((0.1..0.2).contains(&teardown), "{server}");
+}
+
+#[test]
+fn test_all_durations() {
+    let output = run(&["--durations", "0"]).unwrap();
+    let lines = lines(&output);
+    let idx = lines
+        .iter()
+        .position(|l| l == "slowest 2 durations (setup / call / teardown):")
+        .unwrap_or_else(|| panic!("{lines:#?}"));
+    assert!(lines[idx + 1].ends_with(" test_slow"), "{lines:#?}");
+    assert!(lines[idx + 2].ends_with(" test_fast"), "{lines:#?}");
+}
+
+#[test]
+fn test_no_durations() {
+    let output = run(&[]).unwrap();
+    let lines = lines(&output);
+    assert!(!lines.iter().any(|l| l.contains("durations")), "{lines:#?}");
+}
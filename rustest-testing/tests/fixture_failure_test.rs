@@ -0,0 +1,142 @@
+fn run(options: &[&str]) -> std::io::Result<std::process::Output> {
+    let exec = env!("CARGO_BIN_EXE_fixture_failure_test");
+    let mut command = std::process::Command::new(exec);
+    command.env("NO_COLOR", "1");
+    command.args(options);
+    command.output()
+}
+
+#[test]
+fn test_shared_fixture_panic() {
+    let output = run(&[
+        "--nocapture",
+        "--skip",
+        "test_database",
+        "--skip",
+        "test_assertion",
+        "--skip",
+        "test_double",
+    ])
+    .unwrap();
+    assert_eq!(output.status.code(), Some(102));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // The fixture is set up once, all tests using it fail with its panic.
+    assert_eq!(stdout.matches("starting Server").count(), 1, "{stdout}");
+    assert_eq!(
+        stdout
+            .matches("Fixture Server error: setup panicked: Cannot bind port 8080")
+            .count(),
+        3,
+        "{stdout}"
+    );
+    assert!(!stdout.contains("PoisonError"), "{stdout}");
+    assert!(
+        stdout.contains("test result: FAILED. 0 passed; 0 failed; 3 errors;"),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn test_shared_fixture_error() {
+    let output = run(&["--nocapture", "test_database"]).unwrap();
+    assert_eq!(output.status.code(), Some(102));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.matches("connecting Database").count(), 1, "{stdout}");
+    assert_eq!(
+        stdout
+            .matches("Fixture Database error: Connection refused")
+            .count(),
+        2,
+        "{stdout}"
+    );
+}
+
+#[test]
+fn test_error_chain() {
+    let output = run(&["--test-threads", "1", "test_double"]).unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let file = "rustest-testing/src/bin/fixture_failure_test.rs";
+    let expected = format!(
+        "---- test_double[ParamNumber:3] ----\n\
+         Fixture ParamNumber error: invalid number 3\n  \
+         setting up ParamNumber[ParamNumber:3] ({file}:21)\n  \
+         required by Double<ParamNumber>[ParamNumber:3] ({file}:30)\n  \
+         required by test test_double[ParamNumber:3]\n"
+    );
+    assert!(stdout.contains(&expected), "{stdout}");
+}
+
+#[test]
+fn test_assertion_message() {
+    let output = run(&["test_assertion"]).unwrap();
+    assert_eq!(output.status.code(), Some(101));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // The message is printed by the panic hook (in the captured output) only.
+    assert_eq!(
+        stdout.matches("assertion `left == right` failed").count(),
+        1,
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("---- test_assertion stdout ----"),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn test_errors_and_failures() {
+    let output = run(&["--test-threads", "1"]).unwrap();
+    // Failures take precedence over errors in the exit code.
+    assert_eq!(output.status.code(), Some(101));
+    let lines = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|l| l.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect::<Vec<_>>();
+    for expected in [
+        "test test_server ... ERROR",
+        "test test_database ... ERROR",
+        "test test_assertion ... FAILED",
+    ] {
+        assert!(
+            lines.iter().any(|l| l == expected),
+            "{expected}: {lines:#?}"
+        );
+    }
+    assert!(
+        lines.iter().any(|l| l.starts_with(
+            "test result: FAILED. 1 passed; 1 failed; 6 errors; 0 ignored; 0 measured; 0 filtered out;"
+        )),
+        "{lines:#?}"
+    );
+}
+
+#[test]
+fn test_no_tests_collected() {
+    let exec = env!("CARGO_BIN_EXE_empty_test");
+    let output = std::process::Command::new(exec)
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(5));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout
+            .lines()
+            .any(|l| l.starts_with("test result: no tests collected. 0 passed; 0 failed;")),
+        "{stdout}"
+    );
+    let output = std::process::Command::new(exec)
+        .args(["--format", "json"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(5));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains(r#"{"type":"run","event":"finished","outcome":"no_tests","#),
+        "{stdout}"
+    );
+
+    // Tests filtered out are collected.
+    let output = run(&["no_such_test"]).unwrap();
+    assert!(output.status.success());
+}
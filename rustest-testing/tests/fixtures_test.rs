@@ -0,0 +1,40 @@
+fn run(options: &[&str]) -> std::io::Result<std::process::Output> {
+    let exec = env!("CARGO_BIN_EXE_fixtures_test");
+    let mut command = std::process::Command::new(exec);
+    command.env("NO_COLOR", "1");
+    command.args(options);
+    command.output()
+}
+
+#[test]
+fn test_fixtures() {
+    let output = run(&["--fixtures"]).unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let expected = "\
+Port [once] -- rustest-testing/src/bin/fixtures_test.rs:7
+    params: 8080, 8081
+    The port the server listens on.
+
+    Each test gets a new port.
+
+Server [global] -- rustest-testing/src/bin/fixtures_test.rs:13
+    uses: Port
+    A server shared by all the tests.
+
+Unused [test] (unused) -- rustest-testing/src/bin/fixtures_test.rs:18
+
+Server [once] (unused) -- rustest-testing/src/bin/fixtures_test.rs:27
+    Another server, with the same name.
+
+4 fixtures
+";
+    assert_eq!(stdout, expected);
+}
+
+#[test]
+fn test_fixtures_does_not_run_tests() {
+    let output = run(&["--fixtures"]).unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains("test_server"), "{stdout}");
+}
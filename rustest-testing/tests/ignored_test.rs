@@ -10,6 +10,8 @@ fn run(
     let exec = env!("CARGO_BIN_EXE_ignored_test");
     let mut command = std::process::Command::new(exec);
     command.env("NO_COLOR", "1");
+    // Checks are done on the output of the tests and fixtures.
+    command.arg("--nocapture");
     if let Some(envs) = envs {
         for (name, value) in envs {
             command.env(name, value);
@@ -0,0 +1,80 @@
+fn run(options: &[&str]) -> std::io::Result<std::process::Output> {
+    let exec = env!("CARGO_BIN_EXE_isolate_test");
+    let mut command = std::process::Command::new(exec);
+    command.env("NO_COLOR", "1");
+    // Checks are done on the output of the tests and fixtures.
+    command.arg("--nocapture");
+    command.args(options);
+    command.output()
+}
+
+fn lines(output: &std::process::Output) -> Vec<String> {
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|l| l.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect()
+}
+
+fn check_crashes(lines: &[String]) {
+    for expected in [
+        "test test_abort ... FAILED",
+        "test test_segfault ... FAILED",
+        "test test_exit ... FAILED",
+        "test process exited with code 3 without reporting an outcome",
+    ] {
+        assert!(
+            lines.iter().any(|l| l == expected),
+            "{expected}\n{lines:#?}"
+        );
+    }
+    // googletest prints the failure right after the test line.
+    assert!(
+        lines
+            .iter()
+            .any(|l| l.starts_with("test [XFAIL] test_isolated_xfail ...")),
+        "{lines:#?}"
+    );
+    #[cfg(unix)]
+    for expected in [
+        "test process killed by signal 6 (SIGABRT)",
+        "test process killed by signal 11 (SIGSEGV)",
+    ] {
+        assert!(
+            lines.iter().any(|l| l == expected),
+            "{expected}\n{lines:#?}"
+        );
+    }
+    assert!(
+        lines
+            .iter()
+            .any(|l| l.starts_with("test result: FAILED. 2 passed; 4 failed;")),
+        "{lines:#?}"
+    );
+}
+
+#[test]
+fn test_isolated_attr() {
+    let output = run(&["--test-threads", "1"]).unwrap();
+    assert_eq!(output.status.code(), Some(101));
+    let lines = lines(&output);
+    check_crashes(&lines);
+    assert!(
+        lines
+            .iter()
+            .any(|l| l == "test test_number ... OUTPUT test_number"),
+        "{lines:#?}"
+    );
+}
+
+#[test]
+fn test_isolate_all() {
+    let output = run(&["--isolate"]).unwrap();
+    assert_eq!(output.status.code(), Some(101));
+    let lines = lines(&output);
+    check_crashes(&lines);
+    // Output of the isolated test is forwarded.
+    assert!(
+        lines.iter().any(|l| l.ends_with("OUTPUT test_number")),
+        "{lines:#?}"
+    );
+}
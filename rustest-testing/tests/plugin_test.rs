@@ -0,0 +1,46 @@
+fn run(options: &[&str]) -> std::io::Result<std::process::Output> {
+    let exec = env!("CARGO_BIN_EXE_plugin_test");
+    let mut command = std::process::Command::new(exec);
+    command.env("NO_COLOR", "1");
+    command.args(["--test-threads", "1"]);
+    command.args(options);
+    command.output()
+}
+
+fn plugin_events(output: &std::process::Output) -> Vec<String> {
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|l| l.strip_prefix("plugin: "))
+        .map(str::to_owned)
+        .collect()
+}
+
+#[test]
+fn test_plugin_hooks() {
+    let output = run(&[]).unwrap();
+    assert_eq!(output.status.code(), Some(102));
+    assert_eq!(
+        plugin_events(&output),
+        [
+            "collect 2",
+            "start test_client",
+            "setup Server Global ok",
+            "setup Client Once ok",
+            "teardown Client Once ok",
+            "end test_client passed",
+            "start test_broken",
+            "setup Broken Once failed",
+            "end test_broken error",
+            "teardown Server Global ok",
+        ]
+    );
+}
+
+#[test]
+fn test_plugin_collect() {
+    let output = run(&["--list"]).unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("test_client: test"), "{stdout}");
+    assert!(!stdout.contains("test_removed_by_plugin"), "{stdout}");
+}
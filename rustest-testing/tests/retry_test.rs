@@ -0,0 +1,145 @@
+fn run(options: &[&str]) -> std::io::Result<std::process::Output> {
+    let exec = env!("CARGO_BIN_EXE_retry_test");
+    let mut command = std::process::Command::new(exec);
+    command.env("NO_COLOR", "1");
+    command.args(options);
+    command.output()
+}
+
+fn lines(output: &std::process::Output) -> Vec<String> {
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|l| l.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect()
+}
+
+#[test]
+fn test_retries() {
+    let output = run(&["--test-threads", "1"]).unwrap();
+    assert_eq!(output.status.code(), Some(101));
+    let lines = lines(&output);
+    for expected in [
+        "test test_flaky ... FLAKY",
+        "test test_retry_params ... FLAKY",
+        "test result: FAILED. 0 passed; 2 flaky; 2 failed; 0 ignored; 0 measured; 0 filtered out;",
+    ] {
+        assert!(
+            lines.iter().any(|l| l.starts_with(expected)),
+            "{expected}\n{lines:#?}"
+        );
+    }
+}
+
+#[test]
+fn test_default_retries() {
+    let output = run(&["--test-threads", "1", "--retries", "1", "test_fail_once"]).unwrap();
+    assert!(output.status.success());
+    let lines = lines(&output);
+    assert!(
+        lines
+            .iter()
+            .any(|l| l.starts_with("test result: ok. 0 passed; 1 flaky; 0 failed;")),
+        "{lines:#?}"
+    );
+}
+
+#[test]
+fn test_fail_fast() {
+    for option in ["-x", "--fail-fast", "--maxfail=1"] {
+        let output = run(&["--test-threads", "1", option]).unwrap();
+        assert_eq!(output.status.code(), Some(101));
+        let lines = lines(&output);
+        assert!(
+            lines
+                .iter()
+                .any(|l| l == "stopped after 1 failure, 2 tests not run"),
+            "{lines:#?}"
+        );
+        assert!(
+            lines
+                .iter()
+                .any(|l| l.starts_with("test result: FAILED. 0 passed; 1 flaky; 1 failed;")),
+            "{lines:#?}"
+        );
+        assert!(!lines.iter().any(|l| l.contains("test_fail_once")));
+    }
+
+    // Nothing is stopped if we do not reach maxfail.
+    let output = run(&["--test-threads", "1", "--maxfail", "3"]).unwrap();
+    let lines = lines(&output);
+    assert!(!lines.iter().any(|l| l.starts_with("stopped after")));
+}
+
+#[test]
+fn test_last_failed() {
+    let cache_dir = std::env::temp_dir().join(format!("rustest-cache-{}", std::process::id()));
+    let run = |options: &[&str]| {
+        let exec = env!("CARGO_BIN_EXE_retry_test");
+        let output = std::process::Command::new(exec)
+            .env("NO_COLOR", "1")
+            .env("RUSTEST_CACHE_DIR", &cache_dir)
+            .args(["--test-threads", "1"])
+            .args(options)
+            .output()
+            .unwrap();
+        lines(&output)
+    };
+    let tests = |lines: &[String]| {
+        lines
+            .iter()
+            .filter(|l| l.starts_with("test test_"))
+            .map(|l| l.split(' ').nth(1).unwrap().to_owned())
+            .collect::<Vec<_>>()
+    };
+
+    // Without cache, everything is run.
+    let lines = run(&["--lf"]);
+    assert_eq!(
+        tests(&lines),
+        [
+            "test_flaky",
+            "test_always_failing",
+            "test_fail_once",
+            "test_retry_params"
+        ],
+        "{lines:#?}"
+    );
+    // Test binaries of different packages may have the same name.
+    assert!(
+        cache_dir
+            .join("rustest-testing")
+            .join("retry_test.cache")
+            .is_file()
+    );
+
+    let lines = run(&["--lf"]);
+    assert_eq!(
+        tests(&lines),
+        ["test_always_failing", "test_fail_once"],
+        "{lines:#?}"
+    );
+    assert!(
+        lines.iter().any(|l| l.starts_with(
+            "test result: FAILED. 0 passed; 2 failed; 0 ignored; 0 measured; 2 filtered out;"
+        )),
+        "{lines:#?}"
+    );
+
+    // Selected tests are all run if none of them failed.
+    let lines = run(&["--lf", "test_flaky"]);
+    assert_eq!(tests(&lines), ["test_flaky"], "{lines:#?}");
+
+    let lines = run(&["--ff"]);
+    assert_eq!(
+        tests(&lines),
+        [
+            "test_always_failing",
+            "test_fail_once",
+            "test_flaky",
+            "test_retry_params"
+        ],
+        "{lines:#?}"
+    );
+
+    let _ = std::fs::remove_dir_all(&cache_dir);
+}
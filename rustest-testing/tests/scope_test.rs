@@ -27,6 +27,8 @@ fn run(options: Option<&[&str]>) -> std::io::Result<std::process::Output> {
     let exec = env!("CARGO_BIN_EXE_scope_test");
     let mut command = std::process::Command::new(exec);
     command.env("NO_COLOR", "1");
+    // Checks are done on the output of the tests and fixtures.
+    command.arg("--nocapture");
     if let Some(options) = options {
         for opt in options {
             command.arg(opt);
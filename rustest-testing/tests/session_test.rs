@@ -0,0 +1,73 @@
+fn run(options: &[&str], env: &[(&str, &str)]) -> std::io::Result<std::process::Output> {
+    let exec = env!("CARGO_BIN_EXE_session_test");
+    let mut command = std::process::Command::new(exec);
+    command.env("NO_COLOR", "1");
+    command.envs(env.iter().copied());
+    command.args(options);
+    command.output()
+}
+
+fn lines(output: &std::process::Output) -> Vec<String> {
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::to_owned)
+        .collect()
+}
+
+fn position(lines: &[String], pred: impl Fn(&str) -> bool) -> usize {
+    lines
+        .iter()
+        .position(|l| pred(l))
+        .unwrap_or_else(|| panic!("{lines:#?}"))
+}
+
+#[test]
+fn test_session_hooks() {
+    let output = run(&[], &[]).unwrap();
+    assert_eq!(output.status.code(), Some(101));
+    let lines = lines(&output);
+
+    let main = position(&lines, |l| l == "main run");
+    let started = position(&lines, |l| l == "session started");
+    let first_test = position(&lines, |l| l.starts_with("test "));
+    let teardown = position(&lines, |l| l == "Server torn down");
+    let summary = position(&lines, |l| l.starts_with("test result:"));
+    let finished = position(&lines, |l| {
+        l == "session finished: 1 passed, 1 failed, 1 global fixture(s)"
+    });
+    assert!(main < started, "{lines:#?}");
+    assert!(started < first_test, "{lines:#?}");
+    assert!(first_test < teardown, "{lines:#?}");
+    assert!(teardown < summary, "{lines:#?}");
+    assert!(summary < finished, "{lines:#?}");
+}
+
+#[test]
+fn test_session_start_failure() {
+    let output = run(&[], &[("MISSING_PREREQUISITE", "1")]).unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let lines = lines(&output);
+    assert!(lines.contains(&"main run".to_owned()), "{lines:#?}");
+    assert!(!lines.iter().any(|l| l.starts_with("test ")), "{lines:#?}");
+    assert!(
+        !lines.iter().any(|l| l.starts_with("session")),
+        "{lines:#?}"
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Cannot start test session: prerequisite is missing"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn test_session_hooks_not_run_on_list() {
+    let output = run(&["--list"], &[]).unwrap();
+    assert!(output.status.success());
+    let lines = lines(&output);
+    assert!(lines.contains(&"main run".to_owned()), "{lines:#?}");
+    assert!(
+        !lines.iter().any(|l| l.starts_with("session")),
+        "{lines:#?}"
+    );
+}
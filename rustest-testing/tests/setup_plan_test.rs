@@ -0,0 +1,74 @@
+fn run(options: &[&str]) -> std::io::Result<std::process::Output> {
+    let exec = env!("CARGO_BIN_EXE_setup_plan_test");
+    let mut command = std::process::Command::new(exec);
+    command.env("NO_COLOR", "1");
+    command.env("RUST_BACKTRACE", "0");
+    command.args(options);
+    command.output()
+}
+
+#[test]
+fn test_setup_plan() {
+    let output = run(&["--setup-plan", "test_connection"]).unwrap();
+    // No fixture is built (Database panics if it is).
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let expected = "\
+test_connection
+    Connection [once] fresh
+        Database [global] #1, shared by 1 test(s)
+
+1 tests, 1 shared fixture instance(s)
+";
+    assert_eq!(stdout, expected);
+}
+
+#[test]
+fn test_setup_plan_fan_out() {
+    let output = run(&["--setup-plan"]).unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines = stdout.lines().collect::<Vec<_>>();
+    let expected = [
+        "test_matrix[[Mode:3|Size:20]]",
+        "    Database [global] #1, shared by 7 test(s)",
+        "    Mode (Mode:3) [once] fresh",
+        "        Param (Mode:3) [test] fresh",
+        "    Size (Size:20) [once] fresh",
+        "        Param (Size:20) [test] fresh",
+        "",
+        "fan-out:",
+        "    test_matrix: 6 cases from Mode (3) x Size (2)",
+        "",
+        "7 tests, 1 shared fixture instance(s)",
+    ];
+    assert!(lines.ends_with(&expected), "{stdout}");
+}
+
+#[test]
+fn test_setup_plan_dot() {
+    let path = std::env::temp_dir().join(format!("rustest-plan-{}.dot", std::process::id()));
+    let output = run(&["--setup-plan-dot", path.to_str().unwrap()]).unwrap();
+    assert!(output.status.success(), "{output:?}");
+    // Tests are not run.
+    assert!(output.stdout.is_empty());
+    let dot = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let lines = dot.lines().collect::<Vec<_>>();
+    assert_eq!(lines.first(), Some(&"digraph setup_plan {"));
+    assert_eq!(lines.last(), Some(&"}"));
+    for expected in [
+        "  t0 [label=\"test_connection\", shape=box];",
+        "  f1 [label=\"Connection\\n[once]\", style=dashed];",
+        "  s1 [label=\"Database\\n[global] #1, 7 test(s)\"];",
+        "  f1 -> s1;",
+        "  t0 -> f1;",
+        "  t1 [label=\"test_matrix[[Mode:1|Size:10]]\", shape=box];",
+        "  t1 -> s1;",
+        "  t6 -> s1;",
+    ] {
+        assert!(lines.contains(&expected), "{expected}\n{dot}");
+    }
+    // The shared database is a single node.
+    assert_eq!(lines.iter().filter(|l| l.starts_with("  s1 [")).count(), 1);
+}
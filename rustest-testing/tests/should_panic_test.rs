@@ -0,0 +1,44 @@
+fn run(options: &[&str]) -> std::io::Result<std::process::Output> {
+    let exec = env!("CARGO_BIN_EXE_should_panic_test");
+    let mut command = std::process::Command::new(exec);
+    command.env("NO_COLOR", "1");
+    command.args(["--test-threads", "1"]);
+    command.args(options);
+    command.output()
+}
+
+fn lines(output: &std::process::Output) -> Vec<String> {
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|l| l.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect()
+}
+
+#[test]
+fn test_should_panic() {
+    let output = run(&[]).unwrap();
+    assert_eq!(output.status.code(), Some(101));
+    let lines = lines(&output);
+    for expected in [
+        "test test_panics ... ok",
+        "test test_expected ... ok",
+        "test test_expected_name_value ... ok",
+        "test test_expected_arg ... ok",
+        "test test_wrong_panic ... FAILED",
+        "test test_no_panic ... FAILED",
+        "panic message: \"division by zero\"",
+        "expected substring: \"overflow\"",
+        "test did not panic as expected",
+    ] {
+        assert!(
+            lines.iter().any(|l| l == expected),
+            "{expected}: {lines:#?}"
+        );
+    }
+    assert!(
+        lines
+            .iter()
+            .any(|l| l.starts_with("test result: FAILED. 4 passed; 2 failed;")),
+        "{lines:#?}"
+    );
+}
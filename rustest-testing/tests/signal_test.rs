@@ -0,0 +1,139 @@
+#![cfg(unix)]
+
+use std::{
+    io::{BufRead, BufReader, Read},
+    process::{Child, Command, Stdio},
+    time::{Duration, Instant},
+};
+
+/// A run of the tests, killed if still running when dropped.
+struct Run {
+    child: Child,
+    lines: Vec<String>,
+}
+
+impl Run {
+    /// Start the tests, returning once a line ending with `started` has been printed.
+    fn start(options: &[&str], started: &str) -> Self {
+        let exec = env!("CARGO_BIN_EXE_signal_test");
+        let child = Command::new(exec)
+            .env("NO_COLOR", "1")
+            .args(["--test-threads", "1", "--nocapture"])
+            .args(options)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut run = Self {
+            child,
+            lines: Vec::new(),
+        };
+        let mut stdout = BufReader::new(run.child.stdout.as_mut().unwrap());
+        while !run.lines.last().is_some_and(|l| l.ends_with(started)) {
+            let mut line = String::new();
+            assert_ne!(stdout.read_line(&mut line).unwrap(), 0, "{:#?}", run.lines);
+            run.lines.push(line.trim_end().to_owned());
+        }
+        run
+    }
+
+    fn kill(&self, signal: &str) {
+        let status = Command::new("kill")
+            .args([signal, &self.child.id().to_string()])
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    /// Wait for the tests to exit, returning the exit code and the full output (stdout then
+    /// stderr).
+    fn finish(mut self) -> (Option<i32>, Vec<String>) {
+        let mut rest = String::new();
+        let stdout = self.child.stdout.as_mut().unwrap();
+        stdout.read_to_string(&mut rest).unwrap();
+        let stderr = self.child.stderr.as_mut().unwrap();
+        stderr.read_to_string(&mut rest).unwrap();
+        let mut lines = std::mem::take(&mut self.lines);
+        lines.extend(rest.lines().map(|l| l.to_owned()));
+        (self.child.wait().unwrap().code(), lines)
+    }
+}
+
+impl Drop for Run {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[test]
+fn test_interrupt() {
+    let run = Run::start(&[], "Slow test started");
+    run.kill("-INT");
+    let (code, lines) = run.finish();
+    assert_eq!(code, Some(130), "{lines:#?}");
+    // Running test is finished, next ones are not run, and global fixtures are torn down.
+    let mut after_start = lines
+        .iter()
+        .skip_while(|l| !l.ends_with("Slow test started"));
+    assert_eq!(
+        after_start.nth(1).map(String::as_str),
+        Some("ok"),
+        "{lines:#?}"
+    );
+    for expected in [
+        "Teardown of server",
+        "interrupted by signal 2, 3 tests not run",
+    ] {
+        assert!(
+            lines.iter().any(|l| l == expected),
+            "{expected}\n{lines:#?}"
+        );
+    }
+    assert!(!lines.iter().any(|l| l.contains("test_3_not_run")));
+}
+
+#[test]
+fn test_terminate() {
+    let run = Run::start(&[], "Slow test started");
+    run.kill("-TERM");
+    let (code, lines) = run.finish();
+    assert_eq!(code, Some(143), "{lines:#?}");
+    assert!(lines.iter().any(|l| l == "Teardown of server"));
+}
+
+#[test]
+fn test_second_signal_exits() {
+    let run = Run::start(&[], "Slow test started");
+    let start = Instant::now();
+    run.kill("-INT");
+    std::thread::sleep(Duration::from_millis(100));
+    run.kill("-INT");
+    let (code, lines) = run.finish();
+    assert_eq!(code, Some(130), "{lines:#?}");
+    assert!(start.elapsed() < Duration::from_millis(900));
+    assert!(!lines.iter().any(|l| l == "Teardown of server"));
+}
+
+#[test]
+fn test_hanging_test() {
+    // test_5_queued is still waiting to be run (holding the global fixture) when the signal
+    // arrives.
+    let options = ["--skip", "test_2", "--skip", "test_3"];
+    let run = Run::start(&options, "Hanging test started");
+    let start = Instant::now();
+    run.kill("-INT");
+    let (code, lines) = run.finish();
+    assert_eq!(code, Some(130), "{lines:#?}");
+    // Hanging test is given some time, then global fixtures are torn down anyway.
+    assert!(start.elapsed() < Duration::from_secs(10));
+    for expected in [
+        "Teardown of server",
+        "Tests still running 5s after interruption, tearing down global fixtures and exiting",
+    ] {
+        assert!(
+            lines.iter().any(|l| l == expected),
+            "{expected}\n{lines:#?}"
+        );
+    }
+}
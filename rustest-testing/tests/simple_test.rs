@@ -7,6 +7,8 @@ fn run(options: Option<&[&str]>) -> std::io::Result<std::process::Output> {
     let exec = env!("CARGO_BIN_EXE_simple_test");
     let mut command = std::process::Command::new(exec);
     command.env("NO_COLOR", "1");
+    // Checks are done on the output of the tests and fixtures.
+    command.arg("--nocapture");
     if let Some(options) = options {
         for opt in options {
             command.arg(opt);
@@ -258,3 +260,145 @@ test_named[six]: test
         String::from_utf8_lossy(&output.stderr)
     );
 }
+
+#[test]
+fn test_output_shard() {
+    let list = |options: &[&str]| {
+        let output = run(Some(options)).unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .lines()
+            .map(|l| l.to_owned())
+            .collect::<Vec<_>>()
+    };
+    let all = list(&["--list"]);
+    let mut sharded = Vec::new();
+    for shard in ["1/3", "2/3", "3/3"] {
+        let tests = list(&["--list", "--shard", shard]);
+        assert!(tests.len() < all.len(), "{shard}: {tests:?}");
+        // Sharding is deterministic.
+        assert_eq!(tests, list(&["--list", "--shard", shard]));
+        sharded.extend(tests);
+    }
+    sharded.sort();
+    let mut all = all;
+    all.sort();
+    assert_eq!(sharded, all);
+
+    let output = run(Some(&["--shard", "1/3"])).unwrap();
+    assert!(output.status.success());
+    let result = TestCollector::collect(output).result;
+    assert_eq!(result.tested + result.filtered_out, 15);
+    assert!(result.filtered_out > 0);
+}
+
+#[test]
+fn test_output_shuffle() {
+    let order = |options: &[&str]| {
+        let output = run(Some(options)).unwrap();
+        assert!(output.status.success());
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let tests = stdout
+            .lines()
+            .filter_map(|l| {
+                l.strip_prefix("test ")?
+                    .split_once(' ')
+                    .map(|(name, _)| name.to_owned())
+            })
+            .collect::<Vec<_>>();
+        (tests, stdout)
+    };
+    let (unshuffled, _) = order(&["--test-threads", "1"]);
+    let (shuffled, stdout) = order(&["--test-threads", "1", "--seed", "7"]);
+    assert!(
+        stdout.contains("tests have been shuffled with seed 7 (rerun with `--seed 7`)"),
+        "{stdout}"
+    );
+    assert_ne!(shuffled, unshuffled);
+    let (replayed, _) = order(&["--test-threads", "1", "--shuffle", "--seed", "7"]);
+    assert_eq!(replayed, shuffled);
+    let mut sorted = shuffled.clone();
+    sorted.sort();
+    let mut expected = unshuffled.clone();
+    expected.sort();
+    assert_eq!(sorted, expected);
+}
+
+#[test]
+fn test_output_junit() {
+    let report_path = std::env::temp_dir().join(format!(
+        "rustest_simple_test_junit_{}.xml",
+        std::process::id()
+    ));
+    let output = run(Some(&["--junit", report_path.to_str().unwrap()])).unwrap();
+    assert!(output.status.success());
+    let report = std::fs::read_to_string(&report_path).unwrap();
+    std::fs::remove_file(&report_path).unwrap();
+
+    let suite_re = Regex::new(
+        r#"<testsuite name="simple_test" tests="15" failures="0" errors="0" skipped="0" time="[[:digit:]]+.[[:digit:]]+">"#,
+    )
+    .unwrap();
+    assert!(suite_re.is_match(&report), "{report}");
+    let testcase_re = Regex::new(
+        r#"<testcase name="(?<name>[^"]+)" classname="simple_test" time="[[:digit:]]+.[[:digit:]]+"/>"#,
+    )
+    .unwrap();
+    let mut names = testcase_re
+        .captures_iter(&report)
+        .map(|c| c["name"].to_owned())
+        .collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(
+        names,
+        [
+            "test_named[five]",
+            "test_named[six]",
+            "test_number",
+            "test_param_global_number[ParamGlobalNumber:42]",
+            "test_param_global_number[ParamGlobalNumber:5]",
+            "test_param_global_number[ParamGlobalNumber:6]",
+            "test_param_global_number_bis[ParamGlobalNumber:42]",
+            "test_param_global_number_bis[ParamGlobalNumber:5]",
+            "test_param_global_number_bis[ParamGlobalNumber:6]",
+            "test_param_number[ParamNumber:42]",
+            "test_param_number[ParamNumber:5]",
+            "test_param_number[ParamNumber:6]",
+            "test_param_number_bis[ParamNumber:42]",
+            "test_param_number_bis[ParamNumber:5]",
+            "test_param_number_bis[ParamNumber:6]",
+        ]
+    );
+}
+
+#[test]
+fn test_output_json() {
+    let output = run(Some(&[
+        "--format",
+        "json",
+        "--test-threads",
+        "1",
+        "test_param_number[ParamNumber:5]",
+    ]))
+    .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let duration_re =
+        Regex::new(r#""duration":[[:digit:]]+(\.[[:digit:]]+)?(e-?[[:digit:]]+)?"#).unwrap();
+    let lines = stdout
+        .lines()
+        .map(|l| duration_re.replace_all(l, r#""duration":0"#).into_owned())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        lines,
+        [
+            r#"{"type":"run","event":"started","test_count":1,"filtered_out":14}"#,
+            r#"{"type":"test","event":"started","name":"test_param_number[ParamNumber:5]","xfail":false}"#,
+            r#"{"type":"fixture","event":"setup_started","test":"test_param_number[ParamNumber:5]","fixture":"ParamNumber"}"#,
+            r#"{"type":"fixture","event":"setup_finished","test":"test_param_number[ParamNumber:5]","fixture":"ParamNumber","duration":0,"error":null}"#,
+            r#"{"type":"test","event":"finished","name":"test_param_number[ParamNumber:5]","combination":"ParamNumber:5","xfail":false,"ignore":false,"outcome":"passed","duration":0,"failure":null,"output":null}"#,
+            r#"{"type":"run","event":"finished","outcome":"ok","passed":1,"flaky":0,"failed":0,"errors":0,"xpassed":0,"ignored":0,"skipped":0,"measured":0,"filtered_out":14,"not_run":0,"duration":0,"seed":null,"session_errors":[]}"#,
+        ]
+    );
+}
@@ -0,0 +1,68 @@
+fn run(options: &[&str]) -> std::io::Result<std::process::Output> {
+    let exec = env!("CARGO_BIN_EXE_skip_test");
+    let mut command = std::process::Command::new(exec);
+    command.env("NO_COLOR", "1");
+    command.args(["--test-threads", "1"]);
+    command.args(options);
+    command.output()
+}
+
+fn lines(output: &std::process::Output) -> Vec<String> {
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|l| l.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect()
+}
+
+#[test]
+fn test_skip() {
+    let output = run(&[]).unwrap();
+    assert!(output.status.success());
+    let lines = lines(&output);
+    for expected in [
+        "test test_passes ... ok",
+        "test test_skip_in_body ... SKIPPED (not today)",
+        "test test_skip_without_reason ... SKIPPED",
+        "test [XFAIL] test_skip_xfail ... SKIPPED (skipped before failing)",
+    ] {
+        assert!(
+            lines.iter().any(|l| l == expected),
+            "{expected}: {lines:#?}"
+        );
+    }
+    assert!(
+        lines.iter().any(|l| l.starts_with(
+            "test result: ok. 1 passed; 6 skipped; 0 failed; 0 ignored; 0 measured; 0 filtered out;"
+        )),
+        "{lines:#?}"
+    );
+}
+
+#[test]
+fn test_skip_in_shared_fixture() {
+    let output = run(&["--nocapture", "test_tool"]).unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // The global fixture skips once, all tests depending on it are skipped with its reason.
+    assert_eq!(stdout.matches("building Tool").count(), 1, "{stdout}");
+    assert_eq!(
+        stdout
+            .matches("SKIPPED (tool frobnicate is not available)")
+            .count(),
+        3,
+        "{stdout}"
+    );
+}
+
+#[test]
+fn test_skip_isolated() {
+    let output = run(&["--isolate", "test_skip_in_body"]).unwrap();
+    assert!(output.status.success());
+    let lines = lines(&output);
+    assert!(
+        lines
+            .iter()
+            .any(|l| l == "test test_skip_in_body ... SKIPPED (not today)"),
+        "{lines:#?}"
+    );
+}
@@ -0,0 +1,42 @@
+fn run(options: &[&str]) -> std::io::Result<std::process::Output> {
+    let exec = env!("CARGO_BIN_EXE_tags_test");
+    let mut command = std::process::Command::new(exec);
+    command.env("NO_COLOR", "1");
+    command.args(["--test-threads", "1"]);
+    command.args(options);
+    command.output()
+}
+
+fn tests(output: &std::process::Output) -> Vec<String> {
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|l| l.strip_prefix("test "))
+        .filter_map(|l| l.split_whitespace().next())
+        .filter(|name| name.starts_with("test_"))
+        .map(str::to_owned)
+        .collect()
+}
+
+#[test]
+fn test_tags() {
+    for (expr, expected) in [
+        ("slow", &["test_slow", "test_slow_db"][..]),
+        // `db` and `network` are inherited from the fixtures.
+        ("db and not slow", &["test_db"]),
+        ("network", &["test_slow_db", "test_db"]),
+        ("not (network or fast)", &["test_slow", "test_untagged"]),
+        ("fast", &["test_number[Number:1]", "test_number[Number:2]"]),
+    ] {
+        let output = run(&["-m", expr]).unwrap();
+        assert!(output.status.success());
+        assert_eq!(tests(&output), expected, "{expr}");
+    }
+}
+
+#[test]
+fn test_invalid_tags() {
+    let output = run(&["-m", "db and"]).unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Expected a tag"), "{stderr}");
+}
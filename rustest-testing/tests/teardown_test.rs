@@ -0,0 +1,79 @@
+fn run(options: &[&str]) -> std::io::Result<std::process::Output> {
+    let exec = env!("CARGO_BIN_EXE_teardown_test");
+    let mut command = std::process::Command::new(exec);
+    command.env("NO_COLOR", "1");
+    command.args(["--test-threads", "1"]);
+    command.args(options);
+    command.output()
+}
+
+fn lines(output: &std::process::Output) -> Vec<String> {
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|l| l.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect()
+}
+
+#[test]
+fn test_teardown_errors() {
+    let output = run(&["--skip", "test_server"]).unwrap();
+    assert_eq!(output.status.code(), Some(101));
+    let lines = lines(&output);
+    for expected in [
+        "test test_process ... ERROR",
+        "test test_process_failing ... FAILED",
+        "test test_tempdir ... ERROR",
+        "test test_tempdir_failing ... FAILED",
+        "test test_connection ... ok",
+        "Fixture Process teardown error: cannot kill process 42",
+        "Fixture TempDir teardown error: teardown panicked: Cannot remove directory: \
+         Os { code: 2, kind: NotFound, message: \"No such file or directory\" }",
+    ] {
+        assert!(
+            lines.iter().any(|l| l == expected),
+            "{expected}: {lines:#?}"
+        );
+    }
+    // The failure of the test is kept, with the teardown error.
+    assert_eq!(
+        lines
+            .iter()
+            .filter(|l| *l == "Fixture Process teardown error: cannot kill process 42")
+            .count(),
+        2,
+        "{lines:#?}"
+    );
+    assert!(
+        lines.iter().any(|l| l.starts_with(
+            "test result: FAILED. 1 passed; 2 failed; 2 errors; 0 ignored; 0 measured; 1 filtered out;"
+        )),
+        "{lines:#?}"
+    );
+    assert!(!lines.iter().any(|l| l == "session errors:"), "{lines:#?}");
+}
+
+#[test]
+fn test_global_teardown_error() {
+    let output = run(&["test_server"]).unwrap();
+    assert_eq!(output.status.code(), Some(102));
+    let lines = lines(&output);
+    let summary = lines
+        .iter()
+        .position(|l| l.starts_with("test result: FAILED. 1 passed; 0 failed;"))
+        .unwrap_or_else(|| panic!("{lines:#?}"));
+    assert_eq!(
+        lines[summary + 1..summary + 4],
+        [
+            "",
+            "session errors:",
+            "Fixture Server teardown error: port 8080 is still in use"
+        ],
+        "{lines:#?}"
+    );
+}
+
+#[test]
+fn test_teardown_ok() {
+    let output = run(&["test_connection"]).unwrap();
+    assert!(output.status.success());
+}
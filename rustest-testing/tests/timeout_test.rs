@@ -0,0 +1,97 @@
+fn run(options: &[&str]) -> std::io::Result<std::process::Output> {
+    let exec = env!("CARGO_BIN_EXE_timeout_test");
+    let mut command = std::process::Command::new(exec);
+    command.env("NO_COLOR", "1");
+    command.args(options);
+    command.output()
+}
+
+fn lines(output: &std::process::Output) -> Vec<String> {
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|l| l.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect()
+}
+
+#[test]
+fn test_timeout() {
+    let output = run(&["--test-threads", "1"]).unwrap();
+    assert_eq!(output.status.code(), Some(101));
+    let lines = lines(&output);
+    for expected in [
+        "test test_fast ... ok",
+        "test test_hung_body ... TIMEOUT",
+        "test test_hung_fixture ... TIMEOUT",
+        "test test_hung_isolated ... TIMEOUT",
+        "test test_hung_isolated_fixture ... TIMEOUT",
+        "test test_background_process ... ok",
+        "test test_slow ... ok",
+        "test test_hung_body timed out after 0.30s in test body",
+        "test test_hung_fixture timed out after 0.30s in fixture SlowFixture",
+        // Isolated tests report where they are stuck as well.
+        "test test_hung_isolated timed out after 0.30s in test body",
+        "test test_hung_isolated_fixture timed out after 0.30s in fixture SlowFixture",
+    ] {
+        assert!(
+            lines.iter().any(|l| l == expected),
+            "{expected}\n{lines:#?}"
+        );
+    }
+    assert!(
+        lines
+            .iter()
+            .any(|l| l.starts_with("test result: FAILED. 3 passed; 4 failed;")),
+        "{lines:#?}"
+    );
+}
+
+#[test]
+fn test_isolated_timeout() {
+    // Without capture, the processes started by the test share our stderr: we would wait for them
+    // if they were not killed with the test.
+    let start = std::time::Instant::now();
+    let output = run(&["--nocapture", "test_hung_isolated"]).unwrap();
+    assert!(start.elapsed() < std::time::Duration::from_secs(30));
+    assert_eq!(output.status.code(), Some(101));
+    let lines = lines(&output);
+    assert!(
+        lines
+            .iter()
+            .any(|l| l == "test test_hung_isolated timed out after 0.30s in test body"),
+        "{lines:#?}"
+    );
+}
+
+#[test]
+fn test_default_timeout() {
+    let output = run(&["--timeout", "0.1", "test_slow"]).unwrap();
+    assert_eq!(output.status.code(), Some(101));
+    let lines = lines(&output);
+    assert!(
+        lines.iter().any(|l| l == "test test_slow ... TIMEOUT"),
+        "{lines:#?}"
+    );
+    assert!(
+        lines
+            .iter()
+            .any(|l| l == "test test_slow timed out after 0.10s in test body"),
+        "{lines:#?}"
+    );
+
+    // Test timeout takes precedence over the default one.
+    let output = run(&["--timeout", "0.1", "test_fast"]).unwrap();
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_timeout_parallel() {
+    let output = run(&["--test-threads", "4"]).unwrap();
+    assert_eq!(output.status.code(), Some(101));
+    let lines = lines(&output);
+    assert!(
+        lines
+            .iter()
+            .any(|l| l == "test test_hung_fixture ... TIMEOUT"),
+        "{lines:#?}"
+    );
+}
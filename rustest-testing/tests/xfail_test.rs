@@ -0,0 +1,67 @@
+fn run(options: &[&str]) -> std::io::Result<std::process::Output> {
+    let exec = env!("CARGO_BIN_EXE_xfail_test");
+    let mut command = std::process::Command::new(exec);
+    command.env("NO_COLOR", "1");
+    command.args(["--test-threads", "1"]);
+    command.args(options);
+    command.output()
+}
+
+fn lines(output: &std::process::Output) -> Vec<String> {
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|l| l.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect()
+}
+
+#[test]
+fn test_xfail() {
+    let output = run(&[]).unwrap();
+    assert_eq!(output.status.code(), Some(101));
+    let lines = lines(&output);
+    for expected in [
+        "test [XFAIL] test_xfail ... ok",
+        "test [XFAIL] test_xfail_raises ... ok",
+        "test [XFAIL] test_xfail_raises_regex ... ok",
+        "test [XFAIL] test_xfail_raises_substring ... ok",
+        "test [XFAIL] test_xfail_raises_not_regex ... ok",
+        "test [XFAIL] test_xfail_wrong_failure ... FAILED",
+        "test [XFAIL] test_xpass ... XPASS (bug #12)",
+        "test [XFAIL] test_xpass_strict ... XPASS (bug #13)",
+        "test failed, but not as expected (expected a failure matching `overflow`): division by zero",
+        "test passed, but was expected to fail: bug #13",
+    ] {
+        assert!(
+            lines.iter().any(|l| l == expected),
+            "{expected}: {lines:#?}"
+        );
+    }
+    let idx = lines
+        .iter()
+        .rposition(|l| l == "failures:")
+        .unwrap_or_else(|| panic!("{lines:#?}"));
+    assert_eq!(
+        lines[idx + 1..idx + 3],
+        ["test_xfail_wrong_failure", "test_xpass_strict"]
+    );
+    assert!(
+        lines.iter().any(
+            |l| l.starts_with("test result: FAILED. 5 passed; 1 xpassed; 2 failed; 0 ignored;")
+        ),
+        "{lines:#?}"
+    );
+}
+
+#[test]
+fn test_xpass_not_strict() {
+    // A non strict XPASS does not fail the run.
+    let output = run(&["--exact", "test_xpass"]).unwrap();
+    assert!(output.status.success());
+    let lines = lines(&output);
+    assert!(
+        lines
+            .iter()
+            .any(|l| l.starts_with("test result: ok. 0 passed; 1 xpassed; 0 failed; 0 ignored;")),
+        "{lines:#?}"
+    );
+}
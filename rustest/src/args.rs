@@ -0,0 +1,422 @@
+use clap::Parser;
+use std::{path::PathBuf, str::FromStr, time::Duration};
+
+use super::{tags::TagExpr, test::Test};
+
+/// A shard of the tests, `index` is 1-based.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Shard {
+    index: u64,
+    count: u64,
+}
+
+impl FromStr for Shard {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (index, count) = s
+            .split_once('/')
+            .ok_or_else(|| format!("Invalid shard `{s}`, expected K/N"))?;
+        let index: u64 = index
+            .parse()
+            .map_err(|e| format!("Invalid shard index: {e}"))?;
+        let count: u64 = count
+            .parse()
+            .map_err(|e| format!("Invalid shard count: {e}"))?;
+        if index == 0 || index > count {
+            return Err(format!("Shard index must be between 1 and {count}"));
+        }
+        Ok(Self { index, count })
+    }
+}
+
+impl Shard {
+    /// Is the test part of the shard ?
+    ///
+    /// Tests are distributed by a hash of their name, so a test always goes in the same shard
+    /// whatever the other tests are.
+    pub(crate) fn contains(&self, test: &Test) -> bool {
+        stable_hash(test.name()) % self.count == self.index - 1
+    }
+}
+
+/// FNV-1a hash of `s`.
+///
+/// Contrary to std's hashers, this is guaranteed to be the same accross runs, platforms and
+/// rust versions.
+fn stable_hash(s: &str) -> u64 {
+    s.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Command line arguments of a rustest harness.
+///
+/// rustest understands all the arguments of `libtest_mimic` (and so most of the arguments of
+/// the standard harness) plus its own options.
+#[derive(Parser, Debug, Clone, Default)]
+#[command(
+    help_template = "USAGE: [OPTIONS] [FILTER]\n\n{all-args}\n\n\n{after-help}",
+    disable_version_flag = true,
+    mut_arg("nocapture", |arg| arg.help("Don't capture the output of the tests")),
+    mut_arg("show_output", |arg| arg.help("Show the captured output of successful tests")),
+    after_help = "By default, all tests are run in parallel. This can be altered with the \n\
+        --test-threads flag when running tests (set it to 1)."
+)]
+#[group(skip)]
+pub(crate) struct Arguments {
+    #[command(flatten)]
+    pub libtest: libtest_mimic::Arguments,
+
+    /// Path of a JUnit XML report to write at the end of the run.
+    #[arg(
+        long = "junit",
+        value_name = "PATH",
+        env = "RUSTEST_JUNIT",
+        help = "Write a JUnit XML report of the run to PATH"
+    )]
+    pub junit: Option<PathBuf>,
+
+    /// Default time a test is allowed to run.
+    #[arg(
+        long = "timeout",
+        value_name = "SECONDS",
+        env = "RUSTEST_TIMEOUT",
+        value_parser = parse_duration,
+        help = "Report tests running for more than SECONDS as TIMEOUT (unless the test sets its own timeout)"
+    )]
+    pub timeout: Option<Duration>,
+
+    /// Default number of retries of failing tests.
+    #[arg(
+        long = "retries",
+        value_name = "N",
+        env = "RUSTEST_RETRIES",
+        help = "Rerun failing tests up to N times (unless the test sets its own number of retries)"
+    )]
+    pub retries: Option<usize>,
+
+    /// Run only the tests whose tags match an expression.
+    #[arg(
+        short = 'm',
+        long = "tags",
+        value_name = "EXPR",
+        env = "RUSTEST_TAGS",
+        help = "Run only the tests whose tags match EXPR (e.g. \"db and not slow\")"
+    )]
+    pub tags: Option<TagExpr>,
+
+    /// Number of slowest tests to report.
+    #[arg(
+        long = "durations",
+        value_name = "N",
+        help = "Report the N slowest tests and the global fixtures setup and teardown times (0 for all tests)"
+    )]
+    pub durations: Option<usize>,
+
+    /// Run only a shard of the tests.
+    #[arg(
+        long = "shard",
+        value_name = "K/N",
+        env = "RUSTEST_SHARD",
+        help = "Split the tests in N shards and run only the K-th one (1-based)"
+    )]
+    pub shard: Option<Shard>,
+
+    /// Run the tests in a random order.
+    #[arg(long = "shuffle", help = "Run the tests in a random order")]
+    pub shuffle: bool,
+
+    /// Seed of the random order of the tests.
+    #[arg(
+        long = "seed",
+        value_name = "SEED",
+        env = "RUSTEST_SEED",
+        help = "Run the tests in the random order given by SEED (implies --shuffle)"
+    )]
+    pub seed: Option<u64>,
+
+    /// Stop at first failure.
+    #[arg(
+        short = 'x',
+        long = "fail-fast",
+        help = "Do not start new tests after the first failure"
+    )]
+    pub fail_fast: bool,
+
+    /// Stop after N failures.
+    #[arg(
+        long = "maxfail",
+        value_name = "N",
+        value_parser = clap::value_parser!(u64).range(1..),
+        help = "Do not start new tests after N failures"
+    )]
+    pub maxfail: Option<u64>,
+
+    /// Run only the tests which have failed last time.
+    #[arg(
+        long = "lf",
+        alias = "last-failed",
+        help = "Run only the tests which failed last time (or all tests if none failed)"
+    )]
+    pub last_failed: bool,
+
+    /// Run first the tests which have failed last time.
+    #[arg(
+        long = "ff",
+        alias = "failed-first",
+        help = "Run the tests which failed last time first, then the other tests"
+    )]
+    pub failed_first: bool,
+
+    /// List the fixtures instead of running the tests.
+    #[arg(
+        long = "fixtures",
+        help = "List the fixtures defined in the binary (with their docs, scope and dependencies) instead of running the tests"
+    )]
+    pub fixtures: bool,
+
+    /// Show the fixtures the tests would set up instead of running them.
+    #[arg(
+        long = "setup-plan",
+        help = "Show the fixture instances each selected test would set up (and share) instead of running the tests"
+    )]
+    pub setup_plan: bool,
+
+    /// Write the setup plan as a Graphviz DOT file instead of running the tests.
+    #[arg(
+        long = "setup-plan-dot",
+        value_name = "PATH",
+        help = "Write the setup plan of the selected tests as a Graphviz DOT graph to PATH instead of running the tests"
+    )]
+    pub setup_plan_dot: Option<PathBuf>,
+
+    /// Run each test in its own process.
+    #[arg(
+        long = "isolate",
+        help = "Run each test in its own process, so crashes are reported as failures"
+    )]
+    pub isolate: bool,
+
+    /// We are the child process running an isolated test, writing its outcome to this file.
+    #[arg(long = "isolated-child", hide = true, value_name = "OUTCOME_FILE")]
+    pub isolated_child: Option<PathBuf>,
+}
+
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    let secs: f64 = s.parse().map_err(|e| format!("{e}"))?;
+    Duration::try_from_secs_f64(secs).map_err(|e| format!("{e}"))
+}
+
+impl Arguments {
+    /// Parses the global CLI arguments given to the application.
+    pub(crate) fn from_args() -> Self {
+        Parser::parse()
+    }
+
+    /// The seed used to shuffle the tests, if they must be shuffled.
+    pub(crate) fn shuffle_seed(&self) -> Option<u64> {
+        match (self.shuffle, self.seed) {
+            (_, Some(seed)) => Some(seed),
+            (true, None) => Some(fastrand::u64(..)),
+            (false, None) => None,
+        }
+    }
+
+    /// Are tests run (and not only listed) by this process ?
+    pub(crate) fn runs_tests(&self) -> bool {
+        !(self.libtest.list
+            || self.fixtures
+            || self.setup_plan
+            || self.setup_plan_dot.is_some()
+            || self.isolated_child.is_some())
+    }
+
+    /// The number of failures after which no new test is started, if any.
+    pub(crate) fn max_fail(&self) -> Option<u64> {
+        self.maxfail.or(self.fail_fast.then_some(1))
+    }
+
+    /// The time the test is allowed to run, if any.
+    pub(crate) fn timeout_of(&self, test: &Test) -> Option<Duration> {
+        test.timeout().or(self.timeout)
+    }
+
+    /// How many times the test is rerun if it fails.
+    pub(crate) fn retries_of(&self, test: &Test) -> usize {
+        test.retries().or(self.retries).unwrap_or(0)
+    }
+
+    /// Is the test ignored (but still reported) ?
+    pub(crate) fn is_ignored(&self, test: &Test) -> bool {
+        let args = &self.libtest;
+        (test.is_ignored() && !args.ignored && !args.include_ignored) || args.bench
+    }
+
+    /// Is the test filtered out (and not reported) ?
+    pub(crate) fn is_filtered_out(&self, test: &Test) -> bool {
+        let args = &self.libtest;
+        let test_name = test.name();
+        // Match against the full test name, including the kind, so that what `--list`
+        // prints can be used with `--exact`.
+        let test_name_with_kind = test.name_with_kind();
+
+        if let Some(filter) = &args.filter {
+            match args.exact {
+                true if test_name != filter && &test_name_with_kind != filter => return true,
+                false if !test_name_with_kind.contains(filter) => return true,
+                _ => {}
+            };
+        }
+
+        for skip_filter in &args.skip {
+            match args.exact {
+                true if test_name == skip_filter || &test_name_with_kind == skip_filter => {
+                    return true;
+                }
+                false if test_name_with_kind.contains(skip_filter) => return true,
+                _ => {}
+            }
+        }
+
+        if args.ignored && !test.is_ignored() {
+            return true;
+        }
+
+        if let Some(tags) = &self.tags
+            && !tags.matches(test.tags())
+        {
+            return true;
+        }
+
+        if let Some(shard) = &self.shard
+            && !shard.contains(test)
+        {
+            return true;
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verify_cli() {
+        use clap::CommandFactory;
+        Arguments::command().debug_assert();
+    }
+
+    #[test]
+    fn test_junit() {
+        let args = Arguments::parse_from(["test", "--junit", "report.xml", "a_filter"]);
+        assert_eq!(args.junit, Some(PathBuf::from("report.xml")));
+        assert_eq!(args.libtest.filter, Some("a_filter".to_owned()));
+    }
+
+    #[test]
+    fn test_shard() {
+        let args = Arguments::parse_from(["test", "--shard", "2/3"]);
+        assert_eq!(args.shard, Some(Shard { index: 2, count: 3 }));
+        for invalid in ["0/3", "4/3", "1", "a/3", "1/0"] {
+            assert!(
+                Arguments::try_parse_from(["test", "--shard", invalid]).is_err(),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_stable_hash() {
+        // Shards must not change between versions.
+        assert_eq!(stable_hash(""), 0xcbf29ce484222325);
+        assert_eq!(stable_hash("a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(stable_hash("foobar"), 0x85944171f73967e8);
+    }
+
+    #[test]
+    fn test_tags() {
+        let test = |tags: &[&str]| {
+            Test::new("test", false, false, Box::new(|| unreachable!()))
+                .with_tags(tags.iter().map(|t| t.to_string()).collect())
+        };
+        let args = Arguments::parse_from(["test"]);
+        assert!(!args.is_filtered_out(&test(&["slow"])));
+        let args = Arguments::parse_from(["test", "-m", "db and not slow"]);
+        assert!(!args.is_filtered_out(&test(&["db"])));
+        assert!(args.is_filtered_out(&test(&["db", "slow"])));
+        assert!(args.is_filtered_out(&test(&[])));
+        assert!(Arguments::try_parse_from(["test", "--tags", "db and"]).is_err());
+    }
+
+    #[test]
+    fn test_shuffle() {
+        let args = Arguments::parse_from(["test"]);
+        assert_eq!(args.shuffle_seed(), None);
+        let args = Arguments::parse_from(["test", "--shuffle"]);
+        assert!(args.shuffle_seed().is_some());
+        let args = Arguments::parse_from(["test", "--seed", "42"]);
+        assert_eq!(args.shuffle_seed(), Some(42));
+        let args = Arguments::parse_from(["test", "--shuffle", "--seed", "42"]);
+        assert_eq!(args.shuffle_seed(), Some(42));
+    }
+
+    #[test]
+    fn test_max_fail() {
+        assert_eq!(Arguments::parse_from(["test"]).max_fail(), None);
+        assert_eq!(Arguments::parse_from(["test", "-x"]).max_fail(), Some(1));
+        assert_eq!(
+            Arguments::parse_from(["test", "--fail-fast"]).max_fail(),
+            Some(1)
+        );
+        assert_eq!(
+            Arguments::parse_from(["test", "--maxfail", "3"]).max_fail(),
+            Some(3)
+        );
+        assert!(Arguments::try_parse_from(["test", "--maxfail", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_last_failed() {
+        let args = Arguments::parse_from(["test"]);
+        assert!(!args.last_failed && !args.failed_first);
+        let args = Arguments::parse_from(["test", "--lf", "--ff"]);
+        assert!(args.last_failed && args.failed_first);
+        let args = Arguments::parse_from(["test", "--last-failed", "--failed-first"]);
+        assert!(args.last_failed && args.failed_first);
+    }
+
+    #[test]
+    fn test_fixtures() {
+        assert!(!Arguments::parse_from(["test"]).fixtures);
+        assert!(Arguments::parse_from(["test", "--fixtures"]).fixtures);
+    }
+
+    #[test]
+    fn test_setup_plan() {
+        let args = Arguments::parse_from(["test"]);
+        assert!(!args.setup_plan && args.setup_plan_dot.is_none());
+        let args = Arguments::parse_from(["test", "--setup-plan", "--setup-plan-dot", "plan.dot"]);
+        assert!(args.setup_plan);
+        assert_eq!(args.setup_plan_dot, Some(PathBuf::from("plan.dot")));
+    }
+
+    #[test]
+    fn test_durations() {
+        let args = Arguments::parse_from(["test"]);
+        assert_eq!(args.durations, None);
+        let args = Arguments::parse_from(["test", "--durations", "5"]);
+        assert_eq!(args.durations, Some(5));
+        assert!(Arguments::try_parse_from(["test", "--durations", "-1"]).is_err());
+    }
+
+    #[test]
+    fn test_timeout() {
+        let args = Arguments::parse_from(["test", "--timeout", "1.5"]);
+        assert_eq!(args.timeout, Some(Duration::from_millis(1500)));
+        assert!(Arguments::try_parse_from(["test", "--timeout", "-1"]).is_err());
+        assert!(Arguments::try_parse_from(["test", "--timeout", "soon"]).is_err());
+    }
+}
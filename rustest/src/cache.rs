@@ -0,0 +1,152 @@
+//! Outcomes of the previous runs, used by `--lf` and `--ff`.
+//!
+//! The cache is stored in `target/rustest/<package>/<test binary>.cache` (or in
+//! `$RUSTEST_CACHE_DIR/<package>`), one test per line: the outcome, a space and the (json escaped)
+//! name of the test. The package is part of the path as test binaries of different packages may
+//! have the same name.
+//! Tests not run keep the outcome of their last run.
+
+use std::{collections::BTreeMap, path::PathBuf};
+
+use super::report::{Conclusion, Outcome, suite_name};
+
+/// The directory of the cache.
+///
+/// This is the `rustest` directory in cargo's target directory (found by its `CACHEDIR.TAG`
+/// file), unless `RUSTEST_CACHE_DIR` is set.
+fn cache_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("RUSTEST_CACHE_DIR") {
+        return Some(dir.into());
+    }
+    let exe = std::env::current_exe().ok()?;
+    exe.ancestors()
+        .find(|dir| dir.join("CACHEDIR.TAG").is_file())
+        .map(|target| target.join("rustest"))
+}
+
+fn outcome_name(outcome: &Outcome) -> &'static str {
+    match outcome {
+        Outcome::Passed => "passed",
+        Outcome::XFailed(_) => "xfailed",
+        Outcome::XPassed { strict: false, .. } => "xpassed",
+        Outcome::XPassed { strict: true, .. } => "xpassed-strict",
+        Outcome::Failed(_) => "failed",
+        Outcome::Error(_) => "error",
+        Outcome::TimedOut(_) => "timeout",
+        Outcome::Flaky(_) => "flaky",
+        Outcome::Ignored => "ignored",
+        Outcome::Skipped(_) => "skipped",
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Cache {
+    path: Option<PathBuf>,
+    /// Test name -> outcome of its last run.
+    outcomes: BTreeMap<String, String>,
+}
+
+impl Cache {
+    /// Load the cache of the current test binary, from the package `package`.
+    ///
+    /// A missing (or unreadable) cache is an empty cache.
+    pub(crate) fn load(package: Option<&str>) -> Self {
+        let path = cache_dir().map(|dir| {
+            let dir = match package {
+                Some(package) => dir.join(package),
+                None => dir,
+            };
+            dir.join(format!("{}.cache", suite_name()))
+        });
+        let outcomes = path
+            .as_deref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .map(|content| parse(&content))
+            .unwrap_or_default();
+        Self { path, outcomes }
+    }
+
+    /// Has the test failed the last time it was run ?
+    pub(crate) fn has_failed(&self, name: &str) -> bool {
+        matches!(
+            self.outcomes.get(name).map(String::as_str),
+            Some("failed" | "error" | "timeout" | "xpassed-strict")
+        )
+    }
+
+    /// Record the outcomes of a run.
+    pub(crate) fn update(&mut self, conclusion: &Conclusion) {
+        for report in &conclusion.reports {
+            self.outcomes.insert(
+                report.name.clone(),
+                outcome_name(&report.outcome).to_owned(),
+            );
+        }
+    }
+
+    /// Write the cache on disk.
+    pub(crate) fn save(&self) -> std::io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, render(&self.outcomes))
+    }
+}
+
+fn render(outcomes: &BTreeMap<String, String>) -> String {
+    outcomes
+        .iter()
+        .map(|(name, outcome)| format!("{outcome} {}\n", escape8259::escape(name)))
+        .collect()
+}
+
+fn parse(content: &str) -> BTreeMap<String, String> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let (outcome, name) = line.split_once(' ')?;
+            Some((escape8259::unescape(name).ok()?, outcome.to_owned()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let outcomes: BTreeMap<_, _> = [
+            ("test_number", "passed"),
+            ("test_param[ParamFixture:5]", "failed"),
+            ("test_named[a name with spaces]", "timeout"),
+            ("test_named[multi\nline \"quoted\"]", "failed"),
+        ]
+        .into_iter()
+        .map(|(name, outcome)| (name.to_owned(), outcome.to_owned()))
+        .collect();
+        let content = render(&outcomes);
+        assert_eq!(content.lines().count(), 4);
+        assert!(content.contains("failed test_param[ParamFixture:5]\n"));
+        assert_eq!(parse(&content), outcomes);
+    }
+
+    #[test]
+    fn test_has_failed() {
+        let cache = Cache {
+            path: None,
+            outcomes: parse(
+                "passed test_ok\nfailed test_ko\nerror test_db\ntimeout test_hung\nflaky test_flaky\n",
+            ),
+        };
+        assert!(!cache.has_failed("test_ok"));
+        assert!(cache.has_failed("test_ko"));
+        assert!(cache.has_failed("test_db"));
+        assert!(cache.has_failed("test_hung"));
+        assert!(!cache.has_failed("test_flaky"));
+        assert!(!cache.has_failed("unknown"));
+    }
+}
@@ -0,0 +1,162 @@
+//! Capture of the output of the tests.
+//!
+//! Stdout and stderr (file descriptors 1 and 2) are redirected to a temporary file for the whole
+//! run, so the output of tests, fixtures, panic messages and isolated test processes is captured
+//! alike. The output of a test is the part of the file written while the test was running.
+//!
+//! The report itself is written to a duplicate of the original stdout.
+//! Redirection is process wide, so tests are run one at a time while their output is captured
+//! (see [`Capture::lock`]): otherwise the output of a test would include the output of the tests
+//! running at the same time. For the same reason, capture is stopped when a test times out, as it
+//! keeps running in the background (see [`Capture::stop`]).
+
+use std::{
+    fs::File,
+    io,
+    sync::{
+        Mutex, MutexGuard, PoisonError,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+#[cfg(unix)]
+use std::{
+    io::Write,
+    os::{
+        fd::{AsFd, AsRawFd, OwnedFd},
+        unix::fs::FileExt,
+    },
+};
+
+pub(crate) struct Capture {
+    #[cfg(unix)]
+    file: File,
+    #[cfg(unix)]
+    stdout: File,
+    #[cfg(unix)]
+    stderr: OwnedFd,
+    lock: Mutex<()>,
+    stopped: AtomicBool,
+}
+
+impl Capture {
+    /// Lock the capture for the run of a test, `None` if output is not captured anymore.
+    ///
+    /// The output of the test is the output written while the lock is held, so the other tests
+    /// wait for it to be released.
+    pub(crate) fn lock(&self) -> Option<MutexGuard<'_, ()>> {
+        let guard = self.lock.lock().unwrap_or_else(PoisonError::into_inner);
+        (!self.stopped.load(Ordering::Relaxed)).then_some(guard)
+    }
+
+    /// Stop capturing for the rest of the run.
+    ///
+    /// The output captured so far can still be read.
+    pub(crate) fn stop(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+        self.restore();
+    }
+}
+
+#[cfg(unix)]
+fn redirect(from: &impl AsRawFd, to: i32) -> io::Result<()> {
+    // SAFETY: Both file descriptors are valid for the duration of the call.
+    if unsafe { libc::dup2(from.as_raw_fd(), to) } == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+fn flush_std() {
+    let _ = io::stdout().flush();
+    let _ = io::stderr().flush();
+}
+
+#[cfg(unix)]
+impl Capture {
+    /// Redirect stdout and stderr to a new temporary file.
+    pub(crate) fn start() -> io::Result<Self> {
+        let path = std::env::temp_dir().join(format!("rustest-output-{}.txt", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let file = File::options()
+            .read(true)
+            .append(true)
+            .create_new(true)
+            .open(&path)?;
+        // The file stays available as long as it is open.
+        std::fs::remove_file(&path)?;
+
+        flush_std();
+        let stdout = File::from(io::stdout().as_fd().try_clone_to_owned()?);
+        let stderr = io::stderr().as_fd().try_clone_to_owned()?;
+        redirect(&file, libc::STDOUT_FILENO)?;
+        if let Err(e) = redirect(&file, libc::STDERR_FILENO) {
+            let _ = redirect(&stdout, libc::STDOUT_FILENO);
+            return Err(e);
+        }
+        Ok(Self {
+            file,
+            stdout,
+            stderr,
+            lock: Mutex::default(),
+            stopped: AtomicBool::default(),
+        })
+    }
+
+    /// The current position in the captured output.
+    pub(crate) fn position(&self) -> u64 {
+        flush_std();
+        self.file.metadata().map(|m| m.len()).unwrap_or_default()
+    }
+
+    /// The output captured between two positions.
+    pub(crate) fn read(&self, start: u64, end: u64) -> String {
+        let mut buf = vec![0; end.saturating_sub(start) as usize];
+        match self.file.read_exact_at(&mut buf, start) {
+            Ok(()) => String::from_utf8_lossy(&buf).into_owned(),
+            Err(e) => format!("<cannot read captured output: {e}>"),
+        }
+    }
+
+    /// The original stdout, where the report must be written.
+    pub(crate) fn stdout(&self) -> io::Result<File> {
+        self.stdout.try_clone()
+    }
+
+    /// Stop capturing, restoring the original stdout and stderr.
+    pub(crate) fn restore(&self) {
+        flush_std();
+        let _ = redirect(&self.stdout, libc::STDOUT_FILENO);
+        let _ = redirect(&self.stderr, libc::STDERR_FILENO);
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Capture {
+    fn drop(&mut self) {
+        self.restore();
+    }
+}
+
+#[cfg(not(unix))]
+impl Capture {
+    pub(crate) fn start() -> io::Result<Self> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    pub(crate) fn position(&self) -> u64 {
+        0
+    }
+
+    pub(crate) fn read(&self, _start: u64, _end: u64) -> String {
+        String::new()
+    }
+
+    pub(crate) fn stdout(&self) -> io::Result<File> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    pub(crate) fn restore(&self) {}
+}
@@ -0,0 +1,286 @@
+//! Events emitted while tests are run.
+//!
+//! Fixtures are built deep inside the proxies, without any access to the runner.
+//! So the test being run (and where to send its events) is stored in a thread local
+//! for the duration of the test.
+//! The fixtures being setup are tracked the same way, so a runner watching a test can know where
+//! it is stuck.
+
+use std::{
+    cell::{Cell, RefCell},
+    panic::AssertUnwindSafe,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use super::{
+    fixture::{FixtureCreationResult, FixtureScope, TeardownResult},
+    plugin,
+    report::{GlobalFixtureReport, TestReport},
+    test::panic_message,
+};
+
+/// An event of a test run.
+#[derive(Debug, Clone)]
+pub(crate) enum Event {
+    /// A test is about to be run.
+    TestStarted { name: String, xfail: bool },
+
+    /// The setup of a fixture has started.
+    FixtureSetupStarted { test: String, fixture: String },
+
+    /// The setup of a fixture has finished.
+    FixtureSetupFinished {
+        test: String,
+        fixture: String,
+        duration: Duration,
+        error: Option<String>,
+    },
+
+    /// A test has been run.
+    TestFinished(TestReport),
+}
+
+/// Where events are sent.
+pub(crate) type EventSink = Arc<dyn Fn(Event) + Send + Sync>;
+
+/// Called with the fixture pushed on a [FixtureStack], or `None` when a fixture is popped.
+pub(crate) type FixtureWatcher = Arc<dyn Fn(Option<&str>) + Send + Sync>;
+
+/// The fixtures a test is currently setting up, the innermost last.
+#[derive(Clone, Default)]
+pub(crate) struct FixtureStack {
+    fixtures: Arc<Mutex<Vec<String>>>,
+    watcher: Option<FixtureWatcher>,
+}
+
+impl FixtureStack {
+    /// A stack whose changes are also given to `watcher`.
+    pub(crate) fn watched(watcher: FixtureWatcher) -> Self {
+        Self {
+            fixtures: Arc::default(),
+            watcher: Some(watcher),
+        }
+    }
+
+    /// The innermost fixture being set up, if any.
+    pub(crate) fn last(&self) -> Option<String> {
+        self.fixtures.lock().unwrap().last().cloned()
+    }
+
+    fn push(&self, fixture: &str) {
+        self.fixtures.lock().unwrap().push(fixture.to_owned());
+        if let Some(watcher) = &self.watcher {
+            watcher(Some(fixture));
+        }
+    }
+
+    fn pop(&self) {
+        self.fixtures.lock().unwrap().pop();
+        if let Some(watcher) = &self.watcher {
+            watcher(None);
+        }
+    }
+}
+
+struct CurrentTest {
+    name: String,
+    sink: EventSink,
+    fixtures: FixtureStack,
+    teardown_errors: Vec<String>,
+}
+
+/// Time spent by a thread in fixtures which are not part of a test phase.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct FixtureTime {
+    /// Setup of global fixtures.
+    pub global_setup: Duration,
+
+    /// Teardown of (non global) fixtures.
+    pub teardown: Duration,
+}
+
+thread_local! {
+    static CURRENT_TEST: RefCell<Option<CurrentTest>> = const { RefCell::new(None) };
+    static FIXTURE_TIME: Cell<FixtureTime> = Cell::new(FixtureTime::default());
+}
+
+/// Setup and teardown of global fixtures, in setup order.
+static GLOBAL_FIXTURES: Mutex<Vec<GlobalFixtureReport>> = Mutex::new(Vec::new());
+
+/// Teardown errors which are not part of a test (global fixtures).
+static SESSION_ERRORS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// The time spent by the current thread in fixtures since it started.
+///
+/// Differences of two calls tell how much time has been spent in between.
+pub(crate) fn fixture_time() -> FixtureTime {
+    FIXTURE_TIME.get()
+}
+
+/// Take the reports of the global fixtures set up (and torn down) so far.
+pub(crate) fn take_global_fixtures() -> Vec<GlobalFixtureReport> {
+    std::mem::take(&mut GLOBAL_FIXTURES.lock().unwrap())
+}
+
+/// Take the teardown errors which are not part of a test.
+pub(crate) fn take_session_errors() -> Vec<String> {
+    std::mem::take(&mut SESSION_ERRORS.lock().unwrap())
+}
+
+/// Take the errors of the fixtures torn down by the current test so far.
+pub(crate) fn take_teardown_errors() -> Vec<String> {
+    CURRENT_TEST.with_borrow_mut(|current| {
+        current
+            .as_mut()
+            .map(|current| std::mem::take(&mut current.teardown_errors))
+            .unwrap_or_default()
+    })
+}
+
+/// Run `f` as part of the test `name`.
+///
+/// Events emitted by `f` (and fixtures it builds) are sent to `sink`.
+/// Fixtures being setup are pushed on (and popped from) `fixtures`.
+pub(crate) fn with_test<R>(
+    name: &str,
+    sink: &EventSink,
+    fixtures: &FixtureStack,
+    f: impl FnOnce() -> R,
+) -> R {
+    let previous = CURRENT_TEST.replace(Some(CurrentTest {
+        name: name.to_owned(),
+        sink: Arc::clone(sink),
+        fixtures: fixtures.clone(),
+        teardown_errors: Vec::new(),
+    }));
+    let result = f();
+    CURRENT_TEST.set(previous);
+    result
+}
+
+/// Emit an event for the current test (if any).
+///
+/// `event` is called with the name of the current test.
+pub(crate) fn emit(event: impl FnOnce(&str) -> Event) {
+    CURRENT_TEST.with_borrow(|current| {
+        if let Some(current) = current {
+            (current.sink)(event(&current.name))
+        }
+    })
+}
+
+/// Run `f` while marking the current test as being in the fixture `fixture`.
+pub(crate) fn in_fixture<R>(fixture: &str, f: impl FnOnce() -> R) -> R {
+    let fixtures = CURRENT_TEST
+        .with_borrow(|current| current.as_ref().map(|current| current.fixtures.clone()));
+    if let Some(fixtures) = &fixtures {
+        fixtures.push(fixture);
+    }
+    let result = f();
+    if let Some(fixtures) = &fixtures {
+        fixtures.pop();
+    }
+    result
+}
+
+/// Setup a fixture, surrounding it with `FixtureSetupStarted` and `FixtureSetupFinished` events.
+pub(crate) fn fixture_setup<T>(
+    fixture: &str,
+    scope: FixtureScope,
+    setup: impl FnOnce() -> FixtureCreationResult<T>,
+) -> FixtureCreationResult<T> {
+    emit(|test| Event::FixtureSetupStarted {
+        test: test.to_owned(),
+        fixture: fixture.to_owned(),
+    });
+    let start = Instant::now();
+    let result = in_fixture(fixture, setup);
+    let duration = start.elapsed();
+    if let FixtureScope::Global = scope {
+        FIXTURE_TIME.set(FixtureTime {
+            global_setup: FIXTURE_TIME.get().global_setup + duration,
+            ..FIXTURE_TIME.get()
+        });
+        if result.is_ok() {
+            GLOBAL_FIXTURES.lock().unwrap().push(GlobalFixtureReport {
+                name: fixture.to_owned(),
+                setup: duration,
+                teardown: None,
+            });
+        }
+    }
+    for plugin in plugin::plugins() {
+        plugin.on_fixture_setup(fixture, scope, duration, result.as_ref().err());
+    }
+    emit(|test| Event::FixtureSetupFinished {
+        test: test.to_owned(),
+        fixture: fixture.to_owned(),
+        duration,
+        error: result.as_ref().err().map(|e| e.to_string()),
+    });
+    result
+}
+
+/// Run a teardown, turning its panic into an error.
+pub(crate) fn run_teardown(teardown: impl FnOnce() -> TeardownResult) -> TeardownResult {
+    std::panic::catch_unwind(AssertUnwindSafe(teardown)).unwrap_or_else(|payload| {
+        Err(match panic_message(&*payload) {
+            Some(msg) => format!("teardown panicked: {msg}"),
+            None => "teardown panicked".to_owned(),
+        })
+    })
+}
+
+/// Record a teardown error.
+///
+/// Errors of global fixtures (or fixtures torn down outside of a test) are session errors,
+/// others are errors of the current test.
+pub(crate) fn teardown_error(error: String, scope: FixtureScope) {
+    let error = CURRENT_TEST.with_borrow_mut(|current| match current {
+        Some(current) if scope != FixtureScope::Global => {
+            current.teardown_errors.push(error);
+            None
+        }
+        _ => Some(error),
+    });
+    if let Some(error) = error {
+        SESSION_ERRORS.lock().unwrap().push(error);
+    }
+}
+
+/// Teardown a fixture, recording the time spent and its error (if it fails or panics).
+pub(crate) fn fixture_teardown(
+    fixture: &str,
+    scope: FixtureScope,
+    teardown: impl FnOnce() -> TeardownResult,
+) {
+    let start = Instant::now();
+    let result = run_teardown(teardown);
+    let duration = start.elapsed();
+    if let FixtureScope::Global = scope {
+        let mut global_fixtures = GLOBAL_FIXTURES.lock().unwrap();
+        if let Some(report) = global_fixtures
+            .iter_mut()
+            .find(|r| r.name == fixture && r.teardown.is_none())
+        {
+            report.teardown = Some(duration);
+        }
+    } else {
+        FIXTURE_TIME.set(FixtureTime {
+            teardown: FIXTURE_TIME.get().teardown + duration,
+            ..FIXTURE_TIME.get()
+        });
+    }
+    for plugin in plugin::plugins() {
+        plugin.on_fixture_teardown(
+            fixture,
+            scope,
+            duration,
+            result.as_ref().err().map(String::as_str),
+        );
+    }
+    if let Err(e) = result {
+        teardown_error(format!("Fixture {fixture} teardown error: {e}"), scope);
+    }
+}
@@ -0,0 +1,152 @@
+//! Execution of async tests and fixtures.
+//!
+//! Async tests, fixture setups and teardowns are run to completion with [block_on].
+//! By default, a minimal executor polling the future on the calling thread is used.
+//! With the `tokio` feature, futures are run on a tokio runtime shared by the whole run (so
+//! resources created by a global fixture can be used by all the tests).
+
+use std::future::Future;
+
+mod builtin {
+    use std::{
+        future::Future,
+        pin::pin,
+        sync::Arc,
+        task::{Context, Poll, Wake, Waker},
+        thread::{self, Thread},
+    };
+
+    /// Wake a future by unparking the thread polling it.
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+
+        fn wake_by_ref(self: &Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    pub(super) fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                // Spurious wake ups only lead to an extra poll.
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+}
+
+/// The tokio runtime used to run async tests and fixtures.
+///
+/// The runtime is created on first use and lives until the end of the run.
+#[cfg(feature = "tokio")]
+pub fn tokio_runtime() -> &'static tokio::runtime::Runtime {
+    static RUNTIME: std::sync::OnceLock<tokio::runtime::Runtime> = std::sync::OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .expect("Failed to build tokio runtime")
+    })
+}
+
+/// Run a future to completion on the current thread.
+///
+/// This is used by `#[test]` and `#[fixture]` on `async fn`, but it can also be used to call async
+/// code from a sync fixture or test.
+#[cfg(not(feature = "tokio"))]
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    builtin::block_on(future)
+}
+
+/// Run a future to completion on the tokio runtime.
+///
+/// This is used by `#[test]` and `#[fixture]` on `async fn`, but it can also be used to call async
+/// code from a sync fixture or test.
+///
+/// Called from a current thread runtime (which cannot lend its thread), the future is polled on the
+/// calling thread while the runtime is blocked: it must not wait for this runtime (as
+/// `tokio::task::yield_now` does). Tasks it spawns are run by the shared runtime.
+#[cfg(feature = "tokio")]
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    use tokio::runtime::{Handle, RuntimeFlavor};
+
+    // Fixtures may be dropped (and their async teardown run) from an async test.
+    match Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(|| tokio_runtime().block_on(future))
+        }
+        // `block_in_place` panics on a current thread runtime, and the future may not be `Send`.
+        // It is unconstrained as running out of budget would also wait for the blocked runtime.
+        Ok(_) => {
+            let _runtime = tokio_runtime().enter();
+            builtin::block_on(tokio::task::unconstrained(future))
+        }
+        Err(_) => tokio_runtime().block_on(future),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        pin::Pin,
+        task::{Context, Poll},
+        time::Duration,
+    };
+
+    /// A future pending for its `n` first polls, waking itself from another thread.
+    struct Countdown(usize);
+
+    impl Future for Countdown {
+        type Output = &'static str;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            if self.0 == 0 {
+                return Poll::Ready("done");
+            }
+            self.0 -= 1;
+            let waker = cx.waker().clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(1));
+                waker.wake();
+            });
+            Poll::Pending
+        }
+    }
+
+    #[test]
+    fn test_block_on() {
+        assert_eq!(block_on(async { 42 }), 42);
+        assert_eq!(block_on(Countdown(3)), "done");
+        assert_eq!(block_on(async { Countdown(2).await.len() }), 4);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_block_on_in_runtime() {
+        let nested = async { block_on(async { Countdown(2).await.len() }) };
+        assert_eq!(tokio_runtime().block_on(nested), 4);
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let nested = async {
+            block_on(async {
+                // The future is not `Send`.
+                let value = std::rc::Rc::new(40);
+                let spawned = tokio::spawn(async { 2 }).await.unwrap();
+                *value + spawned
+            })
+        };
+        assert_eq!(runtime.block_on(nested), 42);
+    }
+}
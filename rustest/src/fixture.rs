@@ -1,12 +1,16 @@
 use super::{
+    event,
+    plan::FixturePlan,
     proxy_matrix::{CallArgs, Duplicate, ProxyCall, ProxyCombination},
-    test::TestContext,
+    skip::{self, Skipped},
+    test::{TestContext, panic_message},
     test_name::TestName,
 };
 use std::{
     any::{Any, TypeId},
     default::Default,
     ops::Deref,
+    panic::AssertUnwindSafe,
     sync::Arc,
 };
 
@@ -15,6 +19,36 @@ use std::{
 pub struct FixtureCreationError {
     pub fixture_name: String,
     pub error: Arc<dyn std::error::Error + Sync + Send>,
+
+    /// The fixtures being set up when the error happened, from the failing one to the one used
+    /// by the test. It is filled in as the error goes up through the fixtures depending on it.
+    pub chain: Vec<FixtureFrame>,
+}
+
+/// A fixture being set up when a [FixtureCreationError] happened.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixtureFrame {
+    /// The name of the fixture.
+    pub name: String,
+
+    /// The params of the fixture instance, as in test names (if it is parametrized).
+    pub params: Option<String>,
+
+    /// Where the fixture is defined (file and line), if known.
+    pub location: Option<(&'static str, u32)>,
+}
+
+impl std::fmt::Display for FixtureFrame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let Some(params) = &self.params {
+            write!(f, "[{params}]")?;
+        }
+        if let Some((file, line)) = self.location {
+            write!(f, " ({file}:{line})")?;
+        }
+        Ok(())
+    }
 }
 
 /// The result of a fixture creation.
@@ -38,10 +72,88 @@ impl FixtureCreationError {
         Self {
             fixture_name: fixture_name.into(),
             error: Arc::new(error),
+            chain: Vec::new(),
+        }
+    }
+
+    /// The error, as it goes up through the fixture `frame`.
+    pub(crate) fn within(mut self, frame: FixtureFrame) -> Self {
+        self.chain.push(frame);
+        self
+    }
+}
+
+/// The error, followed by the fixtures being set up (the failing one first).
+///
+/// ```text
+/// Fixture ParamFixture error: invalid param 3
+///   setting up ParamFixture[ParamFixture:3] (tests/double.rs:12)
+///   required by Double<ParamFixture>[ParamFixture:3] (tests/double.rs:20)
+/// ```
+impl std::fmt::Display for FixtureCreationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Fixture {} error: {}", self.fixture_name, self.error)?;
+        for (i, frame) in self.chain.iter().enumerate() {
+            let context = if i == 0 { "setting up" } else { "required by" };
+            write!(f, "\n  {context} {frame}")?;
         }
+        Ok(())
     }
 }
 
+/// The panic of a fixture setup.
+#[derive(Debug, Clone)]
+pub(crate) struct SetupPanic(Option<String>);
+
+impl std::fmt::Display for SetupPanic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.0 {
+            Some(msg) => write!(f, "setup panicked: {msg}"),
+            None => write!(f, "setup panicked"),
+        }
+    }
+}
+
+impl std::error::Error for SetupPanic {}
+
+/// Setup a fixture, turning a skip or a panic into an error.
+///
+/// Shared fixtures keep the error, so all tests using them get the same one (instead of a
+/// poisoned fixture).
+pub(crate) fn catch_setup<T>(
+    fixture: &str,
+    setup: impl FnOnce() -> FixtureCreationResult<T>,
+) -> FixtureCreationResult<T> {
+    std::panic::catch_unwind(AssertUnwindSafe(setup)).unwrap_or_else(|payload| {
+        Err(match skip::reason(&*payload) {
+            Some(reason) => FixtureCreationError::new(fixture, Skipped(reason.to_owned())),
+            None => FixtureCreationError::new(
+                fixture,
+                SetupPanic(panic_message(&*payload).map(str::to_owned)),
+            ),
+        })
+    })
+}
+
+/// The name of a fixture type, without the module paths.
+///
+/// `my_crate::Double<my_crate::sub::Param>` is named `Double<Param>`.
+pub(crate) fn fixture_name<F>() -> String {
+    let mut name = String::new();
+    let mut chars = std::any::type_name::<F>().chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == ':' && chars.peek() == Some(&':') {
+            chars.next();
+            while name.ends_with(|c: char| c.is_alphanumeric() || c == '_') {
+                name.pop();
+            }
+        } else {
+            name.push(c);
+        }
+    }
+    name
+}
+
 /// A trait representing a [Fixture] proxy.
 ///
 ///
@@ -74,6 +186,34 @@ pub trait FixtureProxy: Duplicate + TestName {
     fn build(self) -> FixtureCreationResult<Self::Fixt>
     where
         Self: Sized;
+
+    /// The tags of the fixture, including the tags of its sub fixtures.
+    ///
+    /// Tests using the fixture inherit its tags.
+    fn tags() -> Vec<&'static str>
+    where
+        Self: Sized,
+    {
+        Vec::new()
+    }
+
+    /// Where the fixture is defined (file and line), if it is defined with `#[fixture]`.
+    fn location() -> Option<(&'static str, u32)>
+    where
+        Self: Sized,
+    {
+        None
+    }
+
+    /// The fixture instance this proxy would build, for `--setup-plan`.
+    ///
+    /// By default, the instance is built each time it is requested and has no dependencies.
+    fn plan(&self) -> FixturePlan
+    where
+        Self: Sized,
+    {
+        FixturePlan::of(self)
+    }
 }
 
 /// A trait representing a fixture that can be set up and torn down.
@@ -117,7 +257,7 @@ impl<F> SubFixture for F where F: Fixture + 'static {}
 /// Represents the scope of a fixture.
 ///
 /// The scope determines the test's "lifetime" of the fixture.
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum FixtureScope {
     /// Fixture is used only once.
     ///
@@ -139,13 +279,25 @@ pub enum FixtureScope {
     Global,
 }
 
+impl FixtureScope {
+    /// The name of the scope, as written in `#[fixture(scope = ..)]`.
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            FixtureScope::Once => "once",
+            FixtureScope::MatrixUnique => "matrix",
+            FixtureScope::Test => "test",
+            FixtureScope::Global => "global",
+        }
+    }
+}
+
 /// A registry for managing fixtures.
 ///
 /// The `FixtureRegistry` is used to store and manage fixtures. It allows adding and retrieving
 /// fixtures by their type.
 #[derive(Default)]
 pub(crate) struct FixtureRegistry {
-    pub fixtures: std::collections::HashMap<TypeId, Box<dyn Any>>,
+    pub fixtures: std::collections::HashMap<TypeId, Box<dyn Any + Send>>,
 }
 
 impl FixtureRegistry {
@@ -171,7 +323,7 @@ impl FixtureRegistry {
     /// * `F` - The type of the fixture.
     pub(crate) fn add<B>(&mut self, value: Vec<B>)
     where
-        B: FixtureProxy + 'static,
+        B: FixtureProxy + Send + 'static,
     {
         self.fixtures.insert(TypeId::of::<B>(), Box::new(value));
     }
@@ -187,7 +339,7 @@ impl FixtureRegistry {
     /// An option containing a vector of the inner type of the fixture, if found.
     pub(crate) fn get<B>(&mut self) -> Option<Vec<B>>
     where
-        B: FixtureProxy + 'static,
+        B: FixtureProxy + Send + 'static,
     {
         self.fixtures.get(&TypeId::of::<B>()).map(|a| {
             let proxy = a.downcast_ref::<Vec<B>>().unwrap();
@@ -199,7 +351,37 @@ impl FixtureRegistry {
 /// A type alias for a teardown function.
 ///
 /// The teardown function is called when the fixture is dropped to clean up resources.
-pub type TeardownFn<T> = Box<dyn Fn(&mut T) + Send + Sync>;
+pub type TeardownFn<T> = Box<dyn Fn(&mut T) -> TeardownResult + Send + Sync>;
+
+/// The result of a teardown, with the error message if it has failed.
+pub type TeardownResult = std::result::Result<(), String>;
+
+/// What a `teardown` function may return: `()` or a `Result` (whose error is reported).
+pub trait IntoTeardownResult {
+    fn into_teardown_result(self) -> TeardownResult;
+}
+
+impl IntoTeardownResult for () {
+    fn into_teardown_result(self) -> TeardownResult {
+        Ok(())
+    }
+}
+
+impl<E: std::fmt::Display> IntoTeardownResult for std::result::Result<(), E> {
+    fn into_teardown_result(self) -> TeardownResult {
+        self.map_err(|e| e.to_string())
+    }
+}
+
+/// Box a `teardown` function, converting its result. This is called by `#[fixture]`.
+#[doc(hidden)]
+pub fn teardown_fn<T, R, F>(f: F) -> TeardownFn<T>
+where
+    F: Fn(&mut T) -> R + Send + Sync + 'static,
+    R: IntoTeardownResult,
+{
+    Box::new(move |v| f(v).into_teardown_result())
+}
 
 /// A struct that manages the teardown of a fixture.
 ///
@@ -226,7 +408,11 @@ impl<T> std::ops::Deref for FixtureTeardown<T> {
 impl<T> Drop for FixtureTeardown<T> {
     fn drop(&mut self) {
         if let Some(t) = self.teardown.take() {
-            t(&mut self.value)
+            // Teardowns of fixtures report their errors themselves (and never fail), errors left
+            // come from a `FixtureTeardown` built by hand.
+            if let Err(e) = event::run_teardown(|| t(&mut self.value)) {
+                event::teardown_error(format!("Fixture teardown error: {e}"), FixtureScope::Test);
+            }
         }
     }
 }
@@ -238,6 +424,8 @@ impl<T> Drop for FixtureTeardown<T> {
 pub enum LazyValue<V, B> {
     Value(SharedFixtureValue<V>),
     Proxies(Option<ProxyCombination<B>>),
+    /// The value has failed to build (or has been skipped), all users get the same error.
+    Failed(FixtureCreationError),
 }
 
 impl<V, B> From<ProxyCombination<B>> for LazyValue<V, B> {
@@ -247,18 +435,29 @@ impl<V, B> From<ProxyCombination<B>> for LazyValue<V, B> {
 }
 
 impl<V, B> LazyValue<V, B> {
+    /// Has the value already been built (or failed to) ?
+    pub fn is_built(&self) -> bool {
+        matches!(self, LazyValue::Value(_) | LazyValue::Failed(_))
+    }
+
     pub fn get<F, T>(&mut self, f: F) -> FixtureCreationResult<SharedFixtureValue<V>>
     where
         F: Fn(CallArgs<T>) -> FixtureCreationResult<(V, Option<TeardownFn<V>>)>,
         ProxyCombination<B>: ProxyCall<T>,
     {
         if let LazyValue::Proxies(b) = self {
-            let (value, teardown) = b.take().unwrap().call(f)?;
-            *self = LazyValue::Value(SharedFixtureValue::new(value, teardown));
+            let proxies = b
+                .take()
+                .expect("a previous setup of the fixture has been interrupted");
+            *self = match proxies.call(f) {
+                Ok((value, teardown)) => LazyValue::Value(SharedFixtureValue::new(value, teardown)),
+                Err(e) => LazyValue::Failed(e),
+            };
         };
 
         match self {
             LazyValue::Value(v) => Ok(v.clone()),
+            LazyValue::Failed(e) => Err(e.clone()),
             LazyValue::Proxies(_) => unreachable!(),
         }
     }
@@ -289,3 +488,35 @@ impl<T> std::ops::Deref for SharedFixtureValue<T> {
         &self.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::{EventSink, FixtureStack};
+
+    #[test]
+    fn test_failing_teardown() {
+        let sink: EventSink = Arc::new(|_| {});
+        let errors = event::with_test("test", &sink, &FixtureStack::default(), || {
+            drop(FixtureTeardown::new(
+                5,
+                Some(teardown_fn(|_: &mut u32| Err("cannot release 5"))),
+            ));
+            drop(FixtureTeardown::new(
+                6,
+                Some(teardown_fn(|_: &mut u32| -> TeardownResult {
+                    panic!("cannot release 6")
+                })),
+            ));
+            drop(FixtureTeardown::new(7, Some(teardown_fn(|_: &mut u32| ()))));
+            event::take_teardown_errors()
+        });
+        assert_eq!(
+            errors,
+            [
+                "Fixture teardown error: cannot release 5",
+                "Fixture teardown error: teardown panicked: cannot release 6",
+            ]
+        );
+    }
+}
@@ -0,0 +1,44 @@
+//! Metadata of the fixtures defined in the test binary, listed with `--fixtures`.
+//!
+//! Each `#[fixture]` registers its [FixtureInfo] (from a ctor) before main is run.
+
+use std::sync::Mutex;
+
+use super::fixture::FixtureScope;
+
+/// What is known about a fixture defined with `#[fixture]`.
+#[doc(hidden)]
+pub struct FixtureInfo {
+    /// The name of the fixture type.
+    pub name: &'static str,
+
+    /// The doc comment of the fixture.
+    pub doc: &'static str,
+
+    pub scope: FixtureScope,
+
+    /// The types of the fixtures it depends on.
+    pub dependencies: &'static [&'static str],
+
+    /// The names of its parameters (if it is parametrized).
+    pub params: Option<fn() -> Vec<String>>,
+
+    /// Where the fixture is defined.
+    pub file: &'static str,
+    pub line: u32,
+}
+
+static FIXTURES: Mutex<Vec<FixtureInfo>> = Mutex::new(Vec::new());
+
+/// Register a fixture. This is called by `#[fixture]`, you should not call it directly.
+#[doc(hidden)]
+pub fn register_fixture(info: FixtureInfo) {
+    FIXTURES.lock().unwrap().push(info);
+}
+
+/// Take the registered fixtures, sorted by location.
+pub(crate) fn take_fixtures() -> Vec<FixtureInfo> {
+    let mut fixtures = std::mem::take(&mut *FIXTURES.lock().unwrap());
+    fixtures.sort_by_key(|f| (f.file, f.line));
+    fixtures
+}
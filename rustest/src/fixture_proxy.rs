@@ -1,13 +1,15 @@
 use std::{
     marker::PhantomData,
-    sync::{Arc, Mutex},
+    sync::{Arc, Mutex, PoisonError},
 };
 
 use super::{
+    event,
     fixture::{
-        Fixture, FixtureCreationResult, FixtureProxy, FixtureScope, FixtureTeardown, LazyValue,
-        SharedFixtureValue, TeardownFn,
+        Fixture, FixtureCreationResult, FixtureFrame, FixtureProxy, FixtureScope, FixtureTeardown,
+        LazyValue, SharedFixtureValue, TeardownFn, catch_setup, fixture_name,
     },
+    plan::FixturePlan,
     proxy_matrix::{CallArgs, Duplicate, MatrixSetup, ProxyCall, ProxyCombination, ProxyMatrix},
     test_name::TestName,
 };
@@ -25,6 +27,38 @@ pub trait FixtureDef {
     ) -> FixtureCreationResult<<Self::Fixt as Fixture>::Type>;
 
     fn teardown() -> Option<TeardownFn<<Self::Fixt as Fixture>::Type>>;
+
+    /// Where the fixture is defined (file and line).
+    fn location() -> Option<(&'static str, u32)> {
+        None
+    }
+
+    /// The tags of the fixture, including the tags of its sub fixtures.
+    fn tags() -> Vec<&'static str> {
+        Vec::new()
+    }
+}
+
+/// The teardown of the fixture, timed for `--durations`.
+fn timed_teardown<Def: FixtureDef>(name: &str) -> Option<TeardownFn<<Def::Fixt as Fixture>::Type>>
+where
+    <Def::Fixt as Fixture>::Type: 'static,
+{
+    let teardown = Def::teardown()?;
+    let name = name.to_owned();
+    Some(Box::new(move |value| {
+        event::fixture_teardown(&name, Def::SCOPE, || teardown(value));
+        Ok(())
+    }))
+}
+
+/// The fixture `name` being set up, for the chain of a [FixtureCreationError].
+fn frame<Def: FixtureDef>(name: &str, params: &Option<String>) -> FixtureFrame {
+    FixtureFrame {
+        name: name.to_owned(),
+        params: params.clone(),
+        location: Def::location(),
+    }
 }
 
 type InnerLazy<Def> =
@@ -70,6 +104,7 @@ where
 
 impl<Def: FixtureDef + 'static> FixtureProxy for SharedProxy<Def>
 where
+    Self: Send,
     ProxyCombination<Def::SubProxies>: TestName + ProxyCall<Def::SubFixtures>,
     ProxyMatrix<Def::SubProxies>: MatrixSetup<Def::SubProxies>,
     Def::Fixt: From<SharedFixtureValue<<Def::Fixt as Fixture>::Type>>,
@@ -93,13 +128,41 @@ where
     }
 
     fn build(self) -> FixtureCreationResult<Self::Fixt> {
-        let inner = self
-            .inner
-            .lock()
-            .unwrap()
-            .get(|args| Ok((Def::build_fixt(args)?, Def::teardown())))?;
+        let name = fixture_name::<Def::Fixt>();
+        // Another test may be setting up the fixture, waiting for it is part of the setup.
+        let mut lazy = event::in_fixture(&name, || {
+            self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+        });
+        let build = |args| {
+            let value = catch_setup(&name, || Def::build_fixt(args))?;
+            Ok((value, timed_teardown::<Def>(&name)))
+        };
+        let inner = if lazy.is_built() {
+            lazy.get(build)
+        } else {
+            event::fixture_setup(&name, Def::SCOPE, || lazy.get(build))
+        };
+        // The error is kept without this fixture, as it is shared by different dependency chains.
+        let inner = inner.map_err(|e| e.within(frame::<Def>(&name, &self.name)))?;
         Ok(inner.into())
     }
+
+    fn tags() -> Vec<&'static str> {
+        Def::tags()
+    }
+
+    fn location() -> Option<(&'static str, u32)> {
+        Def::location()
+    }
+
+    fn plan(&self) -> FixturePlan {
+        let plan =
+            FixturePlan::of(self).with_shared(Arc::as_ptr(&self.inner) as *const () as usize);
+        match &*self.inner.lock().unwrap_or_else(PoisonError::into_inner) {
+            LazyValue::Proxies(Some(proxies)) => plan.with_dependencies(proxies.plan()),
+            _ => plan,
+        }
+    }
 }
 
 #[doc(hidden)]
@@ -158,7 +221,22 @@ where
     }
 
     fn build(self) -> FixtureCreationResult<Self::Fixt> {
-        let value = self.sub_proxies.call(Def::build_fixt)?;
-        Ok(FixtureTeardown::new(value, Def::teardown()).into())
+        let name = fixture_name::<Def::Fixt>();
+        let build = |args| catch_setup(&name, || Def::build_fixt(args));
+        let value = event::fixture_setup(&name, Def::SCOPE, || self.sub_proxies.call(build))
+            .map_err(|e| e.within(frame::<Def>(&name, &self.name)))?;
+        Ok(FixtureTeardown::new(value, timed_teardown::<Def>(&name)).into())
+    }
+
+    fn tags() -> Vec<&'static str> {
+        Def::tags()
+    }
+
+    fn location() -> Option<(&'static str, u32)> {
+        Def::location()
+    }
+
+    fn plan(&self) -> FixturePlan {
+        FixturePlan::of(self).with_dependencies(self.sub_proxies.plan())
     }
 }
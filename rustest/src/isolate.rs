@@ -0,0 +1,324 @@
+//! Run tests in their own process.
+//!
+//! The test binary is executed again with an exact filter on the test to run (and the hidden
+//! `--isolated-child` flag, giving the path of the outcome file). The child runs the test and
+//! writes its outcome to the outcome file. The child shares our stdout and stderr, so its output is
+//! captured as the output of the test.
+//!
+//! The child runs in its own process group, which is killed if the test times out (so are the
+//! processes started by the test). The child also writes to the outcome file the fixtures it
+//! enters and exits, so we know where a killed test was stuck.
+//! If the child dies without writing its outcome (crash, `std::process::exit`, ...), the test is
+//! reported as failed with the reason of the child's death.
+
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::{Child, Command, ExitCode, ExitStatus, Stdio},
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use super::{
+    args::Arguments,
+    event::{EventSink, FixtureStack, FixtureWatcher},
+    report::Outcome,
+    runner,
+    test::Test,
+};
+
+/// How often a running child is checked for exit.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Prefix of the line written when the child enters a fixture.
+const FIXTURE_ENTERED: &str = "fixture-entered:";
+
+/// Line written when the child exits the innermost fixture it has entered.
+const FIXTURE_EXITED: &str = "fixture-exited:";
+
+/// Encode an outcome as a single line.
+fn encode(outcome: &Outcome) -> String {
+    let (kind, msg) = match outcome {
+        Outcome::Passed => ("passed", ""),
+        Outcome::XFailed(msg) => ("xfailed", msg.as_str()),
+        Outcome::XPassed {
+            reason,
+            strict: false,
+        } => ("xpassed", reason.as_str()),
+        Outcome::XPassed {
+            reason,
+            strict: true,
+        } => ("xpassed-strict", reason.as_str()),
+        Outcome::Failed(msg) => ("failed", msg.as_str()),
+        Outcome::Error(msg) => ("error", msg.as_str()),
+        Outcome::TimedOut(msg) => ("timeout", msg.as_str()),
+        Outcome::Flaky(msg) => ("flaky", msg.as_str()),
+        Outcome::Ignored => ("ignored", ""),
+        Outcome::Skipped(reason) => ("skipped", reason.as_str()),
+    };
+    format!("{kind}:{}", escape8259::escape(msg))
+}
+
+/// Decode an outcome encoded by [encode].
+fn decode(line: &str) -> Option<Outcome> {
+    let (kind, msg) = line.split_once(':')?;
+    let msg = escape8259::unescape(msg).ok()?;
+    Some(match kind {
+        "passed" => Outcome::Passed,
+        "xfailed" => Outcome::XFailed(msg),
+        "xpassed" => Outcome::XPassed {
+            reason: msg,
+            strict: false,
+        },
+        "xpassed-strict" => Outcome::XPassed {
+            reason: msg,
+            strict: true,
+        },
+        "failed" => Outcome::Failed(msg),
+        "error" => Outcome::Error(msg),
+        "timeout" => Outcome::TimedOut(msg),
+        "flaky" => Outcome::Flaky(msg),
+        "ignored" => Outcome::Ignored,
+        "skipped" => Outcome::Skipped(msg),
+        _ => return None,
+    })
+}
+
+#[cfg(unix)]
+fn signal_name(signal: i32) -> Option<&'static str> {
+    Some(match signal {
+        1 => "SIGHUP",
+        2 => "SIGINT",
+        3 => "SIGQUIT",
+        4 => "SIGILL",
+        5 => "SIGTRAP",
+        6 => "SIGABRT",
+        7 => "SIGBUS",
+        8 => "SIGFPE",
+        9 => "SIGKILL",
+        11 => "SIGSEGV",
+        13 => "SIGPIPE",
+        14 => "SIGALRM",
+        15 => "SIGTERM",
+        _ => return None,
+    })
+}
+
+/// Describe why a child process has exited without reporting an outcome.
+fn describe_exit(status: ExitStatus) -> String {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return match signal_name(signal) {
+                Some(name) => format!("test process killed by signal {signal} ({name})"),
+                None => format!("test process killed by signal {signal}"),
+            };
+        }
+    }
+    match status.code() {
+        Some(code) => format!("test process exited with code {code} without reporting an outcome"),
+        None => "test process exited without reporting an outcome".to_owned(),
+    }
+}
+
+/// The file where a child process writes the outcome of its test, removed once dropped.
+struct OutcomeFile(PathBuf);
+
+impl OutcomeFile {
+    fn new() -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        Self(std::env::temp_dir().join(format!(
+            "rustest-outcome-{}-{}.txt",
+            std::process::id(),
+            NEXT_ID.fetch_add(1, Ordering::Relaxed)
+        )))
+    }
+
+    /// The outcome written by the child, if any.
+    fn outcome(&self) -> Option<Outcome> {
+        let content = fs::read_to_string(&self.0).ok()?;
+        content.lines().rev().find_map(decode)
+    }
+
+    /// The innermost fixture the child was in when it has stopped, if any.
+    fn fixture(&self) -> Option<String> {
+        let content = fs::read_to_string(&self.0).ok()?;
+        let mut fixtures = Vec::new();
+        for line in content.lines() {
+            if let Some(fixture) = line.strip_prefix(FIXTURE_ENTERED) {
+                fixtures.push(escape8259::unescape(fixture).unwrap_or_default());
+            } else if line == FIXTURE_EXITED {
+                fixtures.pop();
+            }
+        }
+        fixtures.pop()
+    }
+}
+
+impl Drop for OutcomeFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// Send `signal` to the process group of `child`.
+#[cfg(unix)]
+fn signal_group(child: &Child, signal: i32) {
+    // SAFETY: The child has not been waited for yet, so its process group is still its own.
+    unsafe { libc::kill(-(child.id() as libc::pid_t), signal) };
+}
+
+/// Kill `child` (and the processes it has started).
+fn kill(child: &mut Child) {
+    #[cfg(unix)]
+    signal_group(child, libc::SIGKILL);
+    #[cfg(not(unix))]
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// Wait for `child` to exit, killing it after `timeout`.
+///
+/// Returns `None` if the child has been killed.
+fn wait(child: &mut Child, timeout: Option<Duration>) -> Option<io::Result<ExitStatus>> {
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    #[cfg(unix)]
+    let mut interrupted = false;
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => return Some(Ok(status)),
+            Ok(None) => {}
+            Err(e) => return Some(Err(e)),
+        }
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            kill(child);
+            return None;
+        }
+        // The child is not in our process group, so it does not get the signals sent to it (Ctrl-C).
+        #[cfg(unix)]
+        if let Some(signal) = super::signal::received()
+            && !interrupted
+        {
+            interrupted = true;
+            signal_group(child, signal);
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Run `test` in a child process, killing it after `timeout`.
+pub(crate) fn run_test(test: Test, timeout: Option<Duration>) -> Outcome {
+    let exe = match std::env::current_exe() {
+        Ok(exe) => exe,
+        Err(e) => return Outcome::Failed(format!("Cannot find test executable: {e}")),
+    };
+    let outcome_file = OutcomeFile::new();
+    let mut command = Command::new(exe);
+    command
+        .args([
+            test.name(),
+            "--exact",
+            "--include-ignored",
+            "--test-threads",
+            "1",
+        ])
+        .arg("--isolated-child")
+        .arg(&outcome_file.0)
+        .stdin(Stdio::null());
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+    // The output of the child must come after ours.
+    let _ = io::stdout().flush();
+    let _ = io::stderr().flush();
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => return Outcome::Failed(format!("Cannot spawn test process: {e}")),
+    };
+
+    let Some(status) = wait(&mut child, timeout) else {
+        return runner::timed_out_outcome(
+            test.name(),
+            timeout.unwrap_or_default(),
+            outcome_file.fixture(),
+        );
+    };
+    match (outcome_file.outcome(), status) {
+        (Some(outcome), _) => outcome,
+        (None, Ok(status)) => Outcome::Failed(describe_exit(status)),
+        (None, Err(e)) => Outcome::Failed(format!("Cannot wait for test process: {e}")),
+    }
+}
+
+/// Run the test selected by the parent process and write its outcome to `outcome_file`.
+pub(crate) fn run_child(args: &Arguments, outcome_file: &Path, tests: Vec<Test>) -> ExitCode {
+    let Some(test) = tests.into_iter().find(|test| !args.is_filtered_out(test)) else {
+        eprintln!("No test to run in isolated process");
+        return ExitCode::FAILURE;
+    };
+    let file = match File::options().create(true).append(true).open(outcome_file) {
+        Ok(file) => Arc::new(file),
+        Err(e) => {
+            eprintln!("Cannot open outcome file {}: {e}", outcome_file.display());
+            return ExitCode::FAILURE;
+        }
+    };
+    // Lines are written at once, so the parent never reads half of one.
+    let write_line = move |line: String| (&*file).write_all(format!("{line}\n").as_bytes());
+
+    let watcher: FixtureWatcher = {
+        let write_line = write_line.clone();
+        Arc::new(move |fixture| {
+            let _ = write_line(match fixture {
+                Some(fixture) => format!("{FIXTURE_ENTERED}{}", escape8259::escape(fixture)),
+                None => FIXTURE_EXITED.to_owned(),
+            });
+        })
+    };
+    let name = test.name().to_owned();
+    let sink: EventSink = Arc::new(|_| {});
+    let (outcome, _) = runner::run_test(test, &name, &sink, &FixtureStack::watched(watcher));
+    let _ = io::stdout().flush();
+    let _ = io::stderr().flush();
+    if let Err(e) = write_line(encode(&outcome)) {
+        eprintln!("Cannot write outcome to {}: {e}", outcome_file.display());
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode() {
+        for outcome in [
+            Outcome::Passed,
+            Outcome::XFailed("Boom".to_owned()),
+            Outcome::XPassed {
+                reason: "bug #12".to_owned(),
+                strict: false,
+            },
+            Outcome::XPassed {
+                reason: String::new(),
+                strict: true,
+            },
+            Outcome::Failed("assertion failed\nleft: 1:2\nright: \"3\"".to_owned()),
+            Outcome::Error("Fixture Database error: Connection refused".to_owned()),
+            Outcome::TimedOut("test timed out".to_owned()),
+            Outcome::Ignored,
+            Outcome::Skipped("docker is not installed".to_owned()),
+        ] {
+            let line = encode(&outcome);
+            assert!(!line.contains('\n'));
+            assert_eq!(decode(&line), Some(outcome));
+        }
+        assert_eq!(decode("some test output"), None);
+    }
+}
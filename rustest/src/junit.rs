@@ -0,0 +1,229 @@
+//! JUnit XML report of a run.
+//!
+//! One `<testsuite>` is written per test binary, with one `<testcase>` per generated test.
+
+use std::{fmt::Write as _, io::Write as _, path::Path};
+
+use super::report::{Conclusion, Outcome, TestReport, suite_name};
+
+/// Escape a string to be used as XML text or attribute value.
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Other control chars are not valid XML 1.0 chars.
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c.is_control() => {
+                write!(&mut escaped, "\\u{{{:x}}}", c as u32).expect("Unable to write in string")
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn write_testcase(out: &mut String, suite: &str, report: &TestReport) -> std::fmt::Result {
+    write!(
+        out,
+        r#"    <testcase name="{}" classname="{}" time="{:.3}""#,
+        escape(&report.name),
+        escape(suite),
+        report.duration.as_secs_f64()
+    )?;
+    let first_line = |msg: &str| escape(msg.lines().next().unwrap_or_default());
+    let element = match &report.outcome {
+        Outcome::Passed | Outcome::XPassed { strict: false, .. } => None,
+        Outcome::XPassed { strict: true, .. } => Some(format!(
+            r#"<failure type="xpass" message="{}"/>"#,
+            escape(&report.outcome.failure().unwrap_or_default())
+        )),
+        Outcome::Failed(msg) => Some(format!(
+            r#"<failure message="{}">{}</failure>"#,
+            first_line(msg),
+            escape(msg)
+        )),
+        Outcome::Error(msg) => Some(format!(
+            r#"<error message="{}">{}</error>"#,
+            first_line(msg),
+            escape(msg)
+        )),
+        Outcome::TimedOut(msg) => Some(format!(
+            r#"<failure type="timeout" message="{}"/>"#,
+            escape(msg)
+        )),
+        Outcome::Flaky(msg) => Some(format!(
+            r#"<flakyFailure message="{}">{}</flakyFailure>"#,
+            first_line(msg),
+            escape(msg)
+        )),
+        Outcome::XFailed(msg) => Some(format!(
+            r#"<skipped type="xfail" message="{}"/>"#,
+            first_line(msg)
+        )),
+        Outcome::Ignored => Some(r#"<skipped message="ignored"/>"#.to_owned()),
+        Outcome::Skipped(reason) => Some(format!(r#"<skipped message="{}"/>"#, escape(reason))),
+    };
+    if element.is_none() && report.output.is_empty() {
+        return writeln!(out, "/>");
+    }
+    writeln!(out, ">")?;
+    if let Some(element) = element {
+        writeln!(out, "      {element}")?;
+    }
+    if !report.output.is_empty() {
+        writeln!(
+            out,
+            "      <system-out>{}</system-out>",
+            escape(&report.output)
+        )?;
+    }
+    writeln!(out, "    </testcase>")
+}
+
+/// Render the conclusion of a run as a JUnit XML document.
+pub(crate) fn render(suite: &str, conclusion: &Conclusion) -> String {
+    let skipped = conclusion
+        .reports
+        .iter()
+        .filter(|r| {
+            matches!(
+                r.outcome,
+                Outcome::Ignored | Outcome::Skipped(_) | Outcome::XFailed(_)
+            )
+        })
+        .count();
+    let mut out = String::new();
+    writeln!(&mut out, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
+    writeln!(&mut out, "<testsuites>").unwrap();
+    writeln!(
+        &mut out,
+        r#"  <testsuite name="{}" tests="{}" failures="{}" errors="{}" skipped="{}" time="{:.3}">"#,
+        escape(suite),
+        conclusion.reports.len(),
+        conclusion.num_failed,
+        conclusion.num_errors + conclusion.session_errors.len() as u64,
+        skipped,
+        conclusion.duration.as_secs_f64()
+    )
+    .unwrap();
+    for report in &conclusion.reports {
+        write_testcase(&mut out, suite, report).unwrap();
+    }
+    if !conclusion.session_errors.is_empty() {
+        writeln!(
+            &mut out,
+            "    <system-err>{}</system-err>",
+            escape(&conclusion.session_errors.join("\n"))
+        )
+        .unwrap();
+    }
+    writeln!(&mut out, "  </testsuite>").unwrap();
+    writeln!(&mut out, "</testsuites>").unwrap();
+    out
+}
+
+/// Write the JUnit XML report of a run in `path`.
+pub(crate) fn write_report(path: &Path, conclusion: &Conclusion) -> std::io::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    file.write_all(render(&suite_name(), conclusion).as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn report(name: &str, outcome: Outcome) -> TestReport {
+        TestReport {
+            name: name.to_owned(),
+            combination: None,
+            xfail: matches!(outcome, Outcome::XFailed(_)),
+            ignore: outcome == Outcome::Ignored,
+            outcome,
+            duration: Duration::from_millis(1500),
+            phases: Default::default(),
+            output: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("test[A:1|B:2]"), "test[A:1|B:2]");
+        assert_eq!(
+            escape("a < b && c > \"d\""),
+            "a &lt; b &amp;&amp; c &gt; &quot;d&quot;"
+        );
+        assert_eq!(escape("multi\nline"), "multi\nline");
+        assert_eq!(escape("\u{1b}[31m"), "\\u{1b}[31m");
+    }
+
+    #[test]
+    fn test_render() {
+        let mut conclusion = Conclusion::default();
+        conclusion.add(report("test_ok", Outcome::Passed));
+        conclusion.add(TestReport {
+            output: "Setting up <db>\n".to_owned(),
+            ..report("test_output", Outcome::Passed)
+        });
+        conclusion.add(report(
+            "test[ParamFixture:1|Double:2]",
+            Outcome::Failed("assertion failed\nleft != right".to_owned()),
+        ));
+        conclusion.add(report("test_ignored", Outcome::Ignored));
+        conclusion.add(report(
+            "test_hung",
+            Outcome::TimedOut("test_hung timed out after 1.00s in fixture Slow".to_owned()),
+        ));
+        conclusion.add(report("test_xfail", Outcome::XFailed("Boom".to_owned())));
+        conclusion.add(report(
+            "test_docker",
+            Outcome::Skipped("docker is not <installed>".to_owned()),
+        ));
+        conclusion.add(report(
+            "test_db",
+            Outcome::Error("Fixture Database error: Connection refused".to_owned()),
+        ));
+        conclusion.duration = Duration::from_secs(2);
+        conclusion.session_errors = vec!["Fixture Server teardown error: <port> in use".to_owned()];
+
+        assert_eq!(
+            render("suite", &conclusion),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites>
+  <testsuite name="suite" tests="8" failures="2" errors="2" skipped="3" time="2.000">
+    <testcase name="test_ok" classname="suite" time="1.500"/>
+    <testcase name="test_output" classname="suite" time="1.500">
+      <system-out>Setting up &lt;db&gt;
+</system-out>
+    </testcase>
+    <testcase name="test[ParamFixture:1|Double:2]" classname="suite" time="1.500">
+      <failure message="assertion failed">assertion failed
+left != right</failure>
+    </testcase>
+    <testcase name="test_ignored" classname="suite" time="1.500">
+      <skipped message="ignored"/>
+    </testcase>
+    <testcase name="test_hung" classname="suite" time="1.500">
+      <failure type="timeout" message="test_hung timed out after 1.00s in fixture Slow"/>
+    </testcase>
+    <testcase name="test_xfail" classname="suite" time="1.500">
+      <skipped type="xfail" message="Boom"/>
+    </testcase>
+    <testcase name="test_docker" classname="suite" time="1.500">
+      <skipped message="docker is not &lt;installed&gt;"/>
+    </testcase>
+    <testcase name="test_db" classname="suite" time="1.500">
+      <error message="Fixture Database error: Connection refused">Fixture Database error: Connection refused</error>
+    </testcase>
+    <system-err>Fixture Server teardown error: &lt;port&gt; in use</system-err>
+  </testsuite>
+</testsuites>
+"#
+        );
+    }
+}
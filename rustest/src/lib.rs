@@ -1,4 +1,4 @@
-#![allow(clippy::test_attr_in_doctest)]
+#![allow(clippy::test_attr_in_doctest, clippy::needless_doctest_main)]
 //! rustest, an advance test harness.
 //!
 //! This crate provides mainly three macros ([fixture], [test] and [main]) to set up your tests and their dependencies.
@@ -49,7 +49,7 @@
 //! You also need to add a main function in each of your integration tests. To do so add an empty main function and
 //! mark it with `#[rustest::main]` attribute:
 //!
-//! ```rust
+//! ```rust,no_run
 //! #[rustest::main]
 //! fn main () {}
 //! ```
@@ -62,9 +62,158 @@
 //! fn main() {}
 //! ```
 //!
+//! # Reports
+//!
+//! Besides the human readable output, rustest can write a JUnit XML report of the run with the `--junit <path>`
+//! option (or the `RUSTEST_JUNIT` environment variable). Each generated test (ie each fixture combination) is
+//! reported as its own `<testcase>`.
+//!
+//! ```shell
+//! $ cargo test -- --junit report.xml
+//! ```
+//!
+//! With `--format json`, rustest prints a stream of json objects (one per line) describing the run as it goes:
+//!
+//! * `{"type":"run","event":"started",...}` and `{"type":"run","event":"finished",...}` around the run.
+//! * `{"type":"test","event":"started",...}` and `{"type":"test","event":"finished",...}` around each test.
+//!   The finished event carries the name of the test, its fixture `combination`, its `xfail` and `ignore` flags,
+//!   its `outcome`, its `duration` (in seconds) and its `failure` message (or the reason of a skip).
+//! * `{"type":"fixture","event":"setup_started",...}` and `{"type":"fixture","event":"setup_finished",...}` around
+//!   each fixture setup made for a test.
+//!
+//! This format is not the (unstable) json format of libtest.
+//!
+//! # Output capture
+//!
+//! The output (stdout and stderr) of each test is captured, including the output of its fixtures' setup and teardown
+//! and the panic messages. It is shown only for failing tests, or for all tests with `--show-output`. It is also
+//! part of the json (`output` field) and JUnit (`<system-out>`) reports. `--nocapture` disables capture.
+//!
+//! Output is captured at the process level (on unix only), so **tests are run one at a time while their output is
+//! captured**: `--test-threads` only has an effect with `--nocapture`. A test timing out keeps running in the
+//! background, so the output is not captured anymore once a test has timed out (and the next tests are run in
+//! parallel).
+//!
+//! # Errors and exit codes
+//!
+//! A test whose fixture fails to set up (or to tear down) is reported as `ERROR` (and not `FAILED`): the test itself
+//! has not been run (or is not to blame). Errors are counted apart from failures in the summary and in the reports.
+//! Teardown errors of global fixtures are not part of any test, they are listed after the summary as session errors.
+//!
+//! The test binary exits with:
+//!
+//! * 0 if all tests passed (or have been skipped, ignored, ...),
+//! * 101 if a test failed (as with libtest),
+//! * 102 if no test failed, but some errored (or a global fixture failed to tear down),
+//! * 5 if no test has been collected (as with pytest), the summary then reads `test result: no tests collected`
+//!   (and the `outcome` of the json `run` event is `no_tests`). Filtering out all the tests is not an error,
+//! * 128 + the signal number if the run has been interrupted.
+//!
+//! # Test order
+//!
+//! Tests are run in the order they are declared. With `--shuffle`, they are run in a random order, to detect
+//! hidden dependencies between tests (through global fixtures for instance). The seed used is printed at the end
+//! of the run, and the same order can be replayed with `--seed <seed>` (or the `RUSTEST_SEED` environment variable).
+//!
+//! # Rerunning failed tests
+//!
+//! The outcome of each test is stored in `target/rustest/<package>/` (or in `RUSTEST_CACHE_DIR`). With `--lf` (or
+//! `--last-failed`), only the tests which failed (or timed out) the last time they were run are run again; all the
+//! selected tests are run if none of them failed. With `--ff` (or `--failed-first`), those tests are run first,
+//! followed by the others.
+//!
+//! # Listing fixtures
+//!
+//! `--fixtures` prints the fixtures defined (with [fixture]) in the test binary instead of running the tests. For
+//! each fixture, it shows its scope, location, the fixtures it uses, its params and its doc comment. Fixtures which
+//! are not used by any test are flagged as `(unused)`.
+//!
+//! # Setup plan
+//!
+//! `--setup-plan` prints, for each selected test, the tree of fixture instances it would set up, without building
+//! any of them. Shared instances (test and global scopes) are numbered (`#1`, `#2`, ...) with the number of tests
+//! sharing them, while fixtures built each time they are requested are marked as `fresh`. A final section tells
+//! which fixtures multiply the cases of each parametrized test.
+//!
+//! `--setup-plan-dot <PATH>` writes the same plan as a Graphviz DOT graph (`dot -Tsvg plan.dot > plan.svg`), where
+//! a shared instance is a single node pointed to by all the tests keeping it alive.
+//!
+//! # Durations
+//!
+//! `--durations <N>` prints the N slowest tests (all of them with `--durations 0`) at the end of the run. Each entry
+//! shows the total time of the test and how it splits between the setup of its fixtures, the test body and the
+//! teardown of its fixtures. Global fixtures are not part of any test: their setup and teardown times are listed
+//! separately. Tests run in their own process only report a total time.
+//!
+//! # Skipping at runtime
+//!
+//! [skip!] skips the current test, from its body or from the setup of a fixture it uses. Unlike ignored tests,
+//! this is decided while the test runs, so it can depend on the availability of a tool or of an environment.
+//! Skipped tests are reported as `SKIPPED` with their reason, and are not failures. When a shared fixture skips,
+//! all the tests using it are skipped with the same reason (and the fixture setup is not tried again).
+//!
+//! # Stopping early
+//!
+//! With `--fail-fast` (or `-x`), no new test is started after the first failure. `--maxfail <N>` does the same
+//! after N failures. Tests already running are finished and global fixtures are torn down as usual.
+//!
+//! # Interrupting a run
+//!
+//! On SIGINT (Ctrl-C) or SIGTERM, no new test is started and the tests already running are given 5 seconds to
+//! finish. Global fixtures are then torn down (so spawned servers are stopped and temporary directories removed),
+//! and the process exits with code 128 + the signal number. Fixtures still used by a test which did not finish in
+//! time cannot be torn down. A second signal exits immediately, without any teardown.
+//!
+//! # Session hooks
+//!
+//! The body of the [main] function is run before the tests are collected, which is the place to initialize
+//! logging. If it takes a `&mut` [Session] argument, it can also register hooks: `on_start` hooks are run just
+//! before collection (only when tests are actually run, an error aborts the run), and `on_finish` hooks are given
+//! the [Conclusion] of the run once all tests have run and global fixtures have been torn down.
+//!
+//! ```
+//! use rustest::{test, *};
+//!
+//! #[test]
+//! fn test_42() {}
+//!
+//! #[main]
+//! fn main(session: &mut Session) {
+//!     session
+//!         .on_start(|| {
+//!             if !std::path::Path::new("/").exists() {
+//!                 return Err("the root directory is missing".into());
+//!             }
+//!             Ok(())
+//!         })
+//!         .on_finish(|conclusion| {
+//!             println!("{} passed, {} failed", conclusion.num_passed, conclusion.num_failed);
+//!         });
+//! }
+//! ```
+//!
+//! # Plugins
+//!
+//! A [Plugin] hooks into the run: it can select, reorder or add tests once they are collected, and it is notified
+//! when each test starts and ends and when fixtures are set up and torn down. Plugins are registered from the
+//! [main] function with [Session::plugin], so reporters or resource trackers can be shipped as separate crates.
+//!
+//! # Sharding
+//!
+//! Tests can be split across several machines with `--shard K/N` (or the `RUSTEST_SHARD` environment variable):
+//! the binary runs only the K-th of N shards (1-based). Tests are distributed by a hash of their name (including
+//! the fixture combination), so a test always goes in the same shard. Global fixtures are built only if a test of
+//! the shard uses them.
+//!
+//! ```shell
+//! $ cargo test -- --shard 2/3
+//! ```
+//!
 //! # Feature flags
 //!
 //! * **googletest**: Add support for [googletest](https://crates.io/crates/googletest) matchers. See [Using google test](#using-google-test) section.
+//! * **tokio**: Run async tests and fixtures on a (shared) tokio runtime instead of the built-in executor. See
+//!   [Async tests](macro@test#async-tests).
 //!
 //! # Using google test
 //!
@@ -116,28 +265,62 @@
 //! fn main () {}
 //! ```
 
+mod args;
+mod cache;
+mod capture;
+mod event;
+mod executor;
 mod fixture;
+mod fixture_info;
 mod fixture_proxy;
+mod isolate;
+mod junit;
+mod plan;
+mod plugin;
+mod printer;
 mod proxy_matrix;
+mod report;
+mod runner;
+mod session;
+mod signal;
+mod skip;
+mod tags;
 mod test;
 mod test_name;
 use fixture::FixtureRegistry;
-#[doc(hidden)]
-pub use fixture::SharedFixtureValue;
 pub use fixture::{
-    Fixture, FixtureCreationError, FixtureCreationResult, FixtureProxy, FixtureScope,
-    FixtureTeardown, LazyValue, SubFixture, TeardownFn,
+    Fixture, FixtureCreationError, FixtureCreationResult, FixtureFrame, FixtureProxy, FixtureScope,
+    FixtureTeardown, IntoTeardownResult, LazyValue, SubFixture, TeardownFn, TeardownResult,
 };
 #[doc(hidden)]
+pub use fixture::{SharedFixtureValue, teardown_fn};
+#[doc(hidden)]
+pub use fixture_info::{FixtureInfo, register_fixture};
+#[doc(hidden)]
 pub use fixture_proxy::{FixtureDef, OnceProxy, SharedProxy};
+pub use plan::FixturePlan;
+pub use plugin::Plugin;
 pub use proxy_matrix::Duplicate;
 #[doc(hidden)]
 pub use proxy_matrix::{CallArgs, MatrixSetup, ProxyCall, ProxyCombination, ProxyMatrix};
+pub use report::{Conclusion, GlobalFixtureReport, Outcome, Phases, TestReport};
+pub use session::Session;
+pub use skip::Skipped;
+#[doc(hidden)]
+pub use skip::raise_skip;
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+};
 #[doc(hidden)]
 pub use test::{InnerTestResult, IntoError, TestGenerator, TestRunner};
-pub use test::{Result, Test, TestContext};
+pub use test::{Raises, Result, Test, TestContext, XFail};
 pub use test_name::{ParamName, TestName, ToParamName};
 
+pub use executor::block_on;
+#[cfg(feature = "tokio")]
+pub use executor::tokio_runtime;
+
 pub use ctor::declarative::ctor;
 
 /// Function creating a set of [Test] from a [TestContext].
@@ -150,24 +333,71 @@ pub type TestGeneratorFn = fn(&mut TestContext) -> Vec<Test>;
 ///
 /// You should not directly call it directly.
 /// Use [main] attribute on an empty main function.
-pub fn run_tests(test_generators: &[TestGeneratorFn]) -> std::process::ExitCode {
-    use libtest_mimic::{Arguments, run};
-    let args = Arguments::from_args();
+pub fn run_tests(
+    test_generators: &[TestGeneratorFn],
+    mut session: Session,
+) -> std::process::ExitCode {
+    let args = args::Arguments::from_args();
+    plugin::install(session.take_plugins());
+
+    if args.runs_tests()
+        && let Err(e) = session.start()
+    {
+        eprintln!("Cannot start test session: {e}");
+        return std::process::ExitCode::FAILURE;
+    }
+
+    let global_registry = Arc::new(Mutex::new(FixtureRegistry::new()));
+    let mut used_fixtures = HashSet::new();
+
+    let mut tests = Vec::new();
+    for test_generator in test_generators {
+        let mut test_registry = FixtureRegistry::new();
+        let mut params = FixtureRegistry::new();
+        let mut generated = test_generator(&mut TestContext::new(
+            &mut global_registry.lock().unwrap(),
+            &mut test_registry,
+            &mut params,
+            &mut used_fixtures,
+        ));
 
-    let mut global_registry = FixtureRegistry::new();
+        // Retries must be run with fresh fixtures, so the tests are generated again (with the
+        // same params) when they fail.
+        if generated.iter().any(|t| args.retries_of(t) > 0) {
+            let regenerator = Arc::new(test::Regenerator::new(
+                *test_generator,
+                Arc::clone(&global_registry),
+                params,
+            ));
+            for test in &mut generated {
+                test.set_regenerator(Arc::clone(&regenerator));
+            }
+        }
+        tests.extend(generated);
+    }
 
-    let tests: Vec<_> = test_generators
-        .iter()
-        .flat_map(|test_generator| {
-            let mut test_registry = FixtureRegistry::new();
-            let mut ctx = TestContext::new(&mut global_registry, &mut test_registry);
-            test_generator(&mut ctx).into_iter()
-        })
-        .map(|t| t.into())
-        .collect();
+    for plugin in plugin::plugins() {
+        plugin.on_collect(&mut tests);
+    }
 
-    let conclusion = run(&args, tests);
-    conclusion.exit_code()
+    if args.fixtures {
+        printer::Printer::new(&args, &tests, false, None)
+            .print_fixtures(&fixture_info::take_fixtures(), &used_fixtures);
+        return std::process::ExitCode::SUCCESS;
+    }
+
+    if let Some(outcome_file) = &args.isolated_child {
+        return isolate::run_child(&args, outcome_file, tests);
+    }
+
+    signal::install();
+    match runner::run(&args, tests, global_registry, session.package()) {
+        Some(conclusion) => {
+            session.finish(&conclusion);
+            conclusion.exit_code()
+        }
+        None => std::process::ExitCode::SUCCESS,
+    }
 }
 
 /// Define a fixture that you can use in all `rustest`'s test and fixture arguments.
@@ -272,6 +502,7 @@ pub fn run_tests(test_generators: &[TestGeneratorFn]) -> std::process::ExitCode
 /// `#[fixture(scope=global)]`
 ///
 /// The fixture is created only once. It is shared accross all tests (in a given binary) and teardown at the end.
+/// If its setup fails (or panics), it is not retried: all tests using it fail with the same error.
 ///
 /// ```
 /// use rustest::{test,*};
@@ -474,7 +705,7 @@ pub fn run_tests(test_generators: &[TestGeneratorFn]) -> std::process::ExitCode
 /// But if you parametrized the fixture with a type which is not public this will result in a error about
 /// "private type in public interface". You can specify a custom publicity for param to solve this problem:
 ///
-/// ```
+/// ```no_run
 /// use rustest::{test ,*};
 ///
 /// #[derive(Clone)]
@@ -522,7 +753,56 @@ pub fn run_tests(test_generators: &[TestGeneratorFn]) -> std::process::ExitCode
 /// ```
 ///
 /// The `teardown` value is any expression of type `Fn(&mut T)` where T is your fixture type.
+/// It can also be an async closure (`async |v| ...` or `|v| async move { ... }`).
+///
+/// The teardown may return a `Result` (any error implementing `Display`) instead of `()`. A teardown returning
+/// an error (or panicking) is reported as an `ERROR` of the test which used the fixture (or added to its failure).
+/// Global fixtures are torn down after all tests: their teardown errors are listed after the summary, as session
+/// errors.
+///
+/// ```no_run
+/// # use rustest::{test ,*};
+/// #[fixture(teardown=|pid| std::process::Command::new("kill").arg(pid.to_string()).status().map(|_| ()))]
+/// fn Daemon() -> u32 {
+///     // Spawn the daemon...
+///     1234
+/// }
+///
+/// # #[main]
+/// # fn main() {}
+/// ```
 ///
+/// # Async Fixture
+///
+/// Fixture setup can be an `async fn`. The setup is run to completion with [block_on] when the fixture is built.
+///
+/// ```no_run
+/// # use rustest::{test ,*};
+/// #[fixture(teardown=async |v| println!("Closing {v}"))]
+/// async fn Connection() -> String {
+///     "connection".to_owned()
+/// }
+///
+/// # #[main]
+/// # fn main() {}
+/// ```
+///
+/// # Fixture Tags
+///
+/// Fixtures can be tagged with `tags=[...]`. Tests using a tagged fixture (directly or through other
+/// fixtures) inherit its tags. See [test tags](macro@test#tags).
+///
+/// ```
+/// # use rustest::{test ,*};
+/// #[fixture(tags=[network])]
+/// fn Server() -> u16 { 8080 }
+///
+/// #[test]
+/// fn test_with_server(server: Server) {}
+///
+/// # #[main]
+/// # fn main() {}
+/// ```
 ///
 /// # Fallible Fixture
 ///
@@ -590,7 +870,16 @@ pub use rustest_macro::fixture;
 /// Additional Attributes:
 ///
 /// - Function Attributes:
-///   - [`#[xfail]`](#falling-tests) Expect the test to fail
+///   - [`#[xfail]`](#failing-tests) Expect the test to fail
+///   - [`#[should_panic]`](#panicking-tests) Expect the test to panic
+/// - Test arguments:
+///   - [`xfail`](#failing-tests) Expect the test to fail
+///   - [`should_panic`](#panicking-tests) Expect the test to panic
+///   - [`timeout`](#timeout) Limit the time the test is allowed to run
+///   - [`isolated`](#isolation) Run the test in its own process
+///   - [`retries`](#retries) Rerun the test if it fails
+///   - [`tags`](#tags) Tag the test to select it at run time
+/// - [Async tests](#async-tests)
 ///
 /// ## Injecting Fixtures
 ///
@@ -671,16 +960,180 @@ pub use rustest_macro::fixture;
 /// #[rustest::main]
 /// fn main() {}
 /// ```
+///
+/// ## Failing tests
+///
+/// A test marked with `#[xfail]` (or `#[test(xfail)]`) is expected to fail. It passes if it fails and,
+/// by default, fails if it passes: it is then reported as `XPASS`.
+///
+/// `xfail` takes optional arguments:
+///
+/// - `reason = "..."`: Why the test is expected to fail (shown with `XPASS`).
+/// - `raises = "..."`: A substring of the failure message, or a regex matching it. A test failing for another
+///   reason is a real failure.
+/// - `strict = false`: An `XPASS` is reported, but does not fail the run.
+///
+/// ```
+/// use rustest::{test, *};
+///
+/// #[test(xfail(raises = "overflow"))]
+/// fn test_overflow() {
+///     u8::MAX.checked_add(1).expect("attempt to add with overflow");
+/// }
+///
+/// #[test]
+/// #[xfail(reason = "flaky on CI, see #12", strict = false)]
+/// fn test_known_bug() {
+///     assert_eq!(5 * 6, 31)
+/// }
+///
+/// #[main]
+/// fn main() {}
+/// ```
+///
+/// ## Panicking tests
+///
+/// As with the standard test harness, a test marked with `#[should_panic]` passes only if it panics.
+/// With `#[should_panic(expected = "...")]` (or `#[should_panic = "..."]`), the panic message must also
+/// contain the given string. The same can be written as `#[test(should_panic)]` or
+/// `#[test(should_panic = "...")]`.
+///
+/// ```
+/// use rustest::{test, *};
+///
+/// #[test]
+/// #[should_panic(expected = "overflow")]
+/// fn test_overflow() {
+///     u8::MAX.checked_add(1).expect("attempt to add with overflow");
+/// }
+///
+/// #[main]
+/// fn main() {}
+/// ```
+///
+/// ## Timeout
+///
+/// A test can be given a maximum time to run (fixtures setup included), either as a number of seconds or
+/// as a [`Duration`](std::time::Duration).
+/// A test running over is reported as `TIMEOUT` (naming the fixture it was stuck in, if any) and the run
+/// continues with the other tests.
+///
+/// ```
+/// use rustest::{test, *};
+/// use std::time::Duration;
+///
+/// #[test(timeout = 5)]
+/// fn test_quick() {}
+///
+/// #[test(timeout = Duration::from_millis(500))]
+/// fn test_quicker() {}
+///
+/// #[main]
+/// fn main() {}
+/// ```
+///
+/// A default timeout for all tests can be set with the `--timeout <seconds>` option (or the `RUSTEST_TIMEOUT`
+/// environment variable).
+///
+/// As threads cannot be killed, a test timing out is left running in the background until the end of the run.
+///
+/// ## Isolation
+///
+/// A test marked as `isolated` is run in its own process: the test binary is executed again to run only
+/// this test. A test crashing (segfault, abort, `std::process::exit`, ...) is then reported as failed
+/// (with the signal which has killed it) instead of killing the whole run.
+///
+/// ```
+/// use rustest::{test, *};
+///
+/// #[test(isolated)]
+/// fn test_unsafe_code() {}
+///
+/// #[main]
+/// fn main() {}
+/// ```
+///
+/// All tests can be isolated with the `--isolate` option. Isolated tests timing out are killed.
+///
+/// ## Retries
+///
+/// A failing test can be rerun up to `retries` times. Each attempt is run with freshly built fixtures
+/// (except for global fixtures which are shared by all tests), with the same params.
+/// A test passing on a retry is reported as `FLAKY` (and counted apart from passed tests).
+///
+/// ```
+/// use rustest::{test, *};
+///
+/// #[test(retries = 3)]
+/// fn test_with_network() {}
+///
+/// #[main]
+/// fn main() {}
+/// ```
+///
+/// A default number of retries for all tests can be set with the `--retries <N>` option (or the
+/// `RUSTEST_RETRIES` environment variable).
+///
+/// ## Tags
+///
+/// Tests can be tagged with `tags=[...]`. They also inherit the tags of the fixtures they use.
+/// Tests are selected at run time with `-m <EXPR>` (or the `RUSTEST_TAGS` environment variable),
+/// `EXPR` being a boolean expression of tags with `and`, `or`, `not` and parenthesis.
+///
+/// ```
+/// use rustest::{test, *};
+///
+/// #[test(tags = [slow, db])]
+/// fn test_migration() {}
+///
+/// #[main]
+/// fn main() {}
+/// ```
+///
+/// ```shell
+/// $ cargo test -- -m "db and not slow"
+/// ```
+///
+/// ## Async tests
+///
+/// Tests (and fixtures) can be `async fn`. They are run to completion with [block_on]: by default, a minimal
+/// executor polling the future on the test thread; with the `tokio` feature, a tokio runtime shared by all tests and
+/// fixtures (so a global fixture can hold resources bound to the runtime).
+///
+/// ```
+/// use rustest::{test, *};
+///
+/// #[fixture]
+/// async fn Answer() -> u32 {
+///     42
+/// }
+///
+/// #[test]
+/// async fn test_async(answer: Answer) {
+///     assert_eq!(*answer, 42);
+/// }
+///
+/// #[main]
+/// fn main() {}
+/// ```
 pub use rustest_macro::test;
 
-/// Replace a empty main function into a test harness.
+/// Replace a main function into a test harness.
 ///
-/// ```
+/// ```no_run
 /// use rustest::main;
 /// #[main]
 /// fn main() {}
 /// ```
 ///
-/// Content of the main function is discarded. However you should not have one as it may change in
-/// the future (pre or post run)
+/// The body of the main function is run before the tests are collected. The function may take a
+/// `&mut` [Session] argument to register hooks run before and after the tests.
+///
+/// ```no_run
+/// use rustest::{main, Session};
+/// #[main]
+/// fn main(session: &mut Session) {
+///     session.on_finish(|conclusion| println!("{} tests failed", conclusion.num_failed));
+/// }
+/// ```
 pub use rustest_macro::main;
@@ -0,0 +1,272 @@
+//! The fixtures a test would set up, shown with `--setup-plan` (or exported as Graphviz DOT).
+//!
+//! A plan is built from the proxies of a test, without building any fixture.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write as _,
+    io::{self, Write},
+    path::Path,
+};
+
+use super::{
+    fixture::{FixtureProxy, FixtureScope, fixture_name},
+    test::Test,
+};
+
+/// A fixture instance a test would set up.
+#[derive(Debug, Clone)]
+pub struct FixturePlan {
+    /// The name of the fixture type.
+    pub fixture: String,
+
+    /// The name of the instance (its params), if the fixture is parametrized.
+    pub param: Option<String>,
+
+    pub scope: FixtureScope,
+
+    /// Identifier of the shared value of the instance.
+    ///
+    /// Instances with the same identifier (in the same run) build a single value. This is `None`
+    /// for fixtures built each time they are requested.
+    pub shared: Option<usize>,
+
+    /// The fixture instances this one is built from.
+    pub dependencies: Vec<FixturePlan>,
+}
+
+impl FixturePlan {
+    /// The plan of an instance of `fixture`, without dependencies.
+    pub fn new(fixture: String, param: Option<String>, scope: FixtureScope) -> Self {
+        Self {
+            fixture,
+            param,
+            scope,
+            shared: None,
+            dependencies: Vec::new(),
+        }
+    }
+
+    /// The plan of the instance built by `proxy`, without dependencies.
+    pub fn of<Proxy: FixtureProxy>(proxy: &Proxy) -> Self {
+        Self::new(fixture_name::<Proxy::Fixt>(), proxy.name(), Proxy::SCOPE)
+    }
+
+    /// Set the identifier of the shared value of the instance.
+    pub fn with_shared(self, shared: usize) -> Self {
+        Self {
+            shared: Some(shared),
+            ..self
+        }
+    }
+
+    /// Set the dependencies of the instance.
+    pub fn with_dependencies(self, dependencies: Vec<FixturePlan>) -> Self {
+        Self {
+            dependencies,
+            ..self
+        }
+    }
+
+    /// A key identifying the instance, including its dependencies.
+    fn key(&self) -> String {
+        let mut key = self.label();
+        if let Some(shared) = self.shared {
+            key += &format!("#{shared}");
+        }
+        for dep in &self.dependencies {
+            key += &format!("({})", dep.key());
+        }
+        key
+    }
+
+    pub(crate) fn label(&self) -> String {
+        match &self.param {
+            Some(param) => format!("{} ({param})", self.fixture),
+            None => self.fixture.clone(),
+        }
+    }
+}
+
+/// Short and stable names of shared instances (`#1`, `#2`, ...) in order of appearance, with
+/// the number of tests using them.
+#[derive(Default)]
+pub(crate) struct SharedInstances {
+    ids: HashMap<usize, (usize, usize)>,
+}
+
+impl SharedInstances {
+    pub(crate) fn new(tests: &[Test]) -> Self {
+        fn visit(shared: &mut SharedInstances, plan: &FixturePlan, seen: &mut Vec<usize>) {
+            if let Some(id) = plan.shared
+                && !seen.contains(&id)
+            {
+                seen.push(id);
+                let next = shared.ids.len() + 1;
+                shared.ids.entry(id).or_insert((next, 0)).1 += 1;
+            }
+            for dep in &plan.dependencies {
+                visit(shared, dep, seen);
+            }
+        }
+
+        let mut shared = Self::default();
+        for test in tests {
+            let mut seen = Vec::new();
+            for plan in test.plan() {
+                visit(&mut shared, plan, &mut seen);
+            }
+        }
+        shared
+    }
+
+    /// The number of shared instances.
+    pub(crate) fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// The short name of a shared instance and the number of tests using it.
+    pub(crate) fn get(&self, id: usize) -> (usize, usize) {
+        self.ids[&id]
+    }
+}
+
+/// The cases of a test function.
+pub(crate) struct FanOut {
+    pub function: String,
+    pub num_cases: usize,
+    /// The (direct) fixtures with several instances, with their number of instances.
+    pub fixtures: Vec<(String, usize)>,
+}
+
+/// Where the fan-out of each test function with several cases comes from.
+pub(crate) fn fan_out(tests: &[Test]) -> Vec<FanOut> {
+    let mut functions: Vec<(String, Vec<&Test>)> = Vec::new();
+    for test in tests {
+        let function = test.name().split('[').next().unwrap_or_default();
+        match functions.iter_mut().find(|(name, _)| name == function) {
+            Some((_, cases)) => cases.push(test),
+            None => functions.push((function.to_owned(), vec![test])),
+        }
+    }
+
+    functions
+        .into_iter()
+        .filter(|(_, cases)| cases.len() > 1)
+        .map(|(function, cases)| {
+            let fixtures = cases[0]
+                .plan()
+                .iter()
+                .enumerate()
+                .map(|(idx, plan)| {
+                    let mut keys = cases
+                        .iter()
+                        .filter_map(|case| case.plan().get(idx).map(FixturePlan::key))
+                        .collect::<Vec<_>>();
+                    keys.sort();
+                    keys.dedup();
+                    (plan.fixture.clone(), keys.len())
+                })
+                .filter(|(_, count)| *count > 1)
+                .collect();
+            FanOut {
+                function,
+                num_cases: cases.len(),
+                fixtures,
+            }
+        })
+        .collect()
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Graphviz DOT output of a setup plan.
+struct Dot {
+    dot: String,
+    shared: SharedInstances,
+    /// Shared nodes and edges already written.
+    written: HashSet<String>,
+    num_once: usize,
+}
+
+impl Dot {
+    /// Write the node of a fixture instance (and of its dependencies) and return its id.
+    fn fixture(&mut self, plan: &FixturePlan) -> String {
+        let label = escape(&plan.label());
+        let scope = plan.scope.as_str();
+        let id = match plan.shared {
+            Some(id) => {
+                let (short, count) = self.shared.get(id);
+                let id = format!("s{short}");
+                if !self.written.insert(id.clone()) {
+                    return id;
+                }
+                writeln!(
+                    self.dot,
+                    "  {id} [label=\"{label}\\n[{scope}] #{short}, {count} test(s)\"];"
+                )
+                .unwrap();
+                id
+            }
+            None => {
+                self.num_once += 1;
+                let id = format!("f{}", self.num_once);
+                writeln!(
+                    self.dot,
+                    "  {id} [label=\"{label}\\n[{scope}]\", style=dashed];"
+                )
+                .unwrap();
+                id
+            }
+        };
+        for dep in &plan.dependencies {
+            let dep_id = self.fixture(dep);
+            self.edge(&id, &dep_id);
+        }
+        id
+    }
+
+    fn edge(&mut self, from: &str, to: &str) {
+        let edge = format!("  {from} -> {to};");
+        if self.written.insert(edge.clone()) {
+            writeln!(self.dot, "{edge}").unwrap();
+        }
+    }
+}
+
+/// Format the setup plan of the tests as a Graphviz DOT graph.
+///
+/// Tests are boxes pointing to the fixture instances they use. A shared instance is a single
+/// node, whatever the number of tests using it, while fixtures built each time they are
+/// requested get a (dashed) node per use.
+pub(crate) fn to_dot(tests: &[Test]) -> String {
+    let mut dot = Dot {
+        dot: String::from("digraph setup_plan {\n  rankdir=LR;\n  node [shape=ellipse];\n"),
+        shared: SharedInstances::new(tests),
+        written: HashSet::new(),
+        num_once: 0,
+    };
+    for (idx, test) in tests.iter().enumerate() {
+        let test_id = format!("t{idx}");
+        writeln!(
+            dot.dot,
+            "  {test_id} [label=\"{}\", shape=box];",
+            escape(test.name())
+        )
+        .unwrap();
+        for plan in test.plan() {
+            let fixture_id = dot.fixture(plan);
+            dot.edge(&test_id, &fixture_id);
+        }
+    }
+    dot.dot.push_str("}\n");
+    dot.dot
+}
+
+/// Write the setup plan of the tests as a Graphviz DOT file.
+pub(crate) fn write_dot(path: &Path, tests: &[Test]) -> io::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    file.write_all(to_dot(tests).as_bytes())
+}
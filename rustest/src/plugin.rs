@@ -0,0 +1,99 @@
+//! Plugins hooking into the collection and the execution of the tests.
+//!
+//! Plugins are registered on the [Session](crate::Session) from the `#[main]` function and
+//! installed for the whole run. Fixtures are built deep inside the proxies, so installed plugins
+//! are kept in a global to be reachable from there.
+
+use std::{sync::OnceLock, time::Duration};
+
+use super::{
+    fixture::{FixtureCreationError, FixtureScope},
+    report::TestReport,
+    test::Test,
+};
+
+/// Hooks into the collection and the execution of the tests.
+///
+/// All hooks do nothing by default. Tests are run in parallel, so hooks may be called from
+/// several threads at the same time (use interior mutability to keep a state).
+///
+/// ```no_run
+/// use rustest::{test, *};
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// /// Run only the first 10 tests and count the fixtures set up.
+/// #[derive(Default)]
+/// struct Sample {
+///     setups: AtomicUsize,
+/// }
+///
+/// impl Plugin for Sample {
+///     fn on_collect(&self, tests: &mut Vec<Test>) {
+///         tests.truncate(10);
+///     }
+///
+///     fn on_fixture_setup(
+///         &self,
+///         _fixture: &str,
+///         _scope: FixtureScope,
+///         _duration: std::time::Duration,
+///         _error: Option<&FixtureCreationError>,
+///     ) {
+///         self.setups.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// #[main]
+/// fn main(session: &mut Session) {
+///     session.plugin(Sample::default());
+/// }
+/// ```
+pub trait Plugin: Send + Sync {
+    /// Called once all tests are collected, before they are filtered.
+    ///
+    /// Tests can be removed, reordered or added.
+    fn on_collect(&self, _tests: &mut Vec<Test>) {}
+
+    /// Called before a test (and its retries) is run.
+    fn on_test_start(&self, _test: &Test) {}
+
+    /// Called once a test has been run, with its report (and outcome).
+    fn on_test_end(&self, _report: &TestReport) {}
+
+    /// Called once a fixture has been set up (or has failed to).
+    ///
+    /// For isolated tests, this is called in the process running the test.
+    fn on_fixture_setup(
+        &self,
+        _fixture: &str,
+        _scope: FixtureScope,
+        _duration: Duration,
+        _error: Option<&FixtureCreationError>,
+    ) {
+    }
+
+    /// Called once a fixture has been torn down (or has failed to), only fixtures with a
+    /// teardown are.
+    ///
+    /// For isolated tests, this is called in the process running the test.
+    fn on_fixture_teardown(
+        &self,
+        _fixture: &str,
+        _scope: FixtureScope,
+        _duration: Duration,
+        _error: Option<&str>,
+    ) {
+    }
+}
+
+static PLUGINS: OnceLock<Vec<Box<dyn Plugin>>> = OnceLock::new();
+
+/// Install the plugins of the run. Only the first call has an effect.
+pub(crate) fn install(plugins: Vec<Box<dyn Plugin>>) {
+    let _ = PLUGINS.set(plugins);
+}
+
+/// The installed plugins, in registration order.
+pub(crate) fn plugins() -> &'static [Box<dyn Plugin>] {
+    PLUGINS.get().map(Vec::as_slice).unwrap_or_default()
+}
@@ -0,0 +1,691 @@
+//! Everything printed to the screen (or logfile) during a run.
+//!
+//! The output mimics the one of libtest (and libtest_mimic), so tools parsing it
+//! continue to work.
+
+use std::{collections::HashSet, fs::File, io::Write};
+
+use anstream::AutoStream;
+use anstyle::{AnsiColor, Color, Style};
+use libtest_mimic::{ColorSetting, FormatSetting};
+
+use super::{
+    args::Arguments,
+    event::Event,
+    fixture_info::FixtureInfo,
+    plan::{self, FixturePlan, SharedInstances},
+    report::{Conclusion, Outcome, TestReport},
+    test::Test,
+};
+
+pub(crate) struct Printer {
+    out: Box<dyn Write + Send>,
+    format: FormatSetting,
+    name_width: usize,
+    kind_width: usize,
+
+    /// Print the captured output of successful tests too ?
+    show_output: bool,
+
+    /// Are tests run in parallel ?
+    ///
+    /// If so, "test foo ... " is printed only once the test is finished to not
+    /// interleave output of different tests.
+    threaded: bool,
+}
+
+impl Printer {
+    /// Creates a new printer configured by the given arguments (`format`,
+    /// `quiet`, `color`, `logfile` and `show-output` options).
+    ///
+    /// `stdout` is where to print if there is no logfile (the process' stdout by default).
+    pub(crate) fn new(
+        args: &Arguments,
+        tests: &[Test],
+        threaded: bool,
+        stdout: Option<File>,
+    ) -> Self {
+        let args = &args.libtest;
+        let color_arg = args.color.unwrap_or(ColorSetting::Auto);
+
+        let out: Box<dyn Write + Send> = if let Some(logfile) = &args.logfile {
+            let f = File::create(logfile).expect("failed to create logfile");
+            if color_arg == ColorSetting::Always {
+                Box::new(AutoStream::always(f))
+            } else {
+                Box::new(AutoStream::never(f))
+            }
+        } else {
+            let choice = match color_arg {
+                ColorSetting::Auto => anstream::ColorChoice::Auto,
+                ColorSetting::Always => anstream::ColorChoice::Always,
+                ColorSetting::Never => anstream::ColorChoice::Never,
+            };
+            match stdout {
+                Some(stdout) => Box::new(AutoStream::new(stdout, choice)),
+                None => Box::new(AutoStream::new(std::io::stdout(), choice)),
+            }
+        };
+
+        let format = if args.quiet {
+            FormatSetting::Terse
+        } else {
+            args.format.unwrap_or(FormatSetting::Pretty)
+        };
+
+        // Counting the number of code points is a cheap way to align names which works
+        // in most cases.
+        let name_width = tests
+            .iter()
+            .map(|test| test.name().chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let kind_width = tests
+            .iter()
+            .map(|test| {
+                if test.kind().is_empty() {
+                    0
+                } else {
+                    // The two braces [] and one space
+                    test.kind().chars().count() + 3
+                }
+            })
+            .max()
+            .unwrap_or(0);
+
+        Self {
+            out,
+            format,
+            name_width,
+            kind_width,
+            show_output: args.show_output,
+            threaded,
+        }
+    }
+
+    /// Prints the first line "running 3 tests".
+    pub(crate) fn print_title(&mut self, num_tests: usize, num_filtered_out: u64) {
+        match self.format {
+            FormatSetting::Pretty | FormatSetting::Terse => {
+                let plural_s = if num_tests == 1 { "" } else { "s" };
+
+                writeln!(self.out).unwrap();
+                writeln!(self.out, "running {} test{}", num_tests, plural_s).unwrap();
+            }
+            FormatSetting::Json => writeln!(
+                self.out,
+                r#"{{"type":"run","event":"started","test_count":{},"filtered_out":{}}}"#,
+                num_tests, num_filtered_out
+            )
+            .unwrap(),
+        }
+    }
+
+    /// Prints the text announcing the test (e.g. "test foo::bar ... "). Prints
+    /// nothing in terse mode.
+    pub(crate) fn print_test(&mut self, name: &str, kind: &str) {
+        match self.format {
+            FormatSetting::Pretty => {
+                let kind = if kind.is_empty() {
+                    String::new()
+                } else {
+                    format!("[{}] ", kind)
+                };
+
+                write!(
+                    self.out,
+                    "test {: <2$}{: <3$} ... ",
+                    kind, name, self.kind_width, self.name_width,
+                )
+                .unwrap();
+                self.out.flush().unwrap();
+            }
+            FormatSetting::Terse | FormatSetting::Json => {}
+        }
+    }
+
+    /// Prints an event of the run.
+    ///
+    /// In json format, every event is printed as a json object on its own line.
+    pub(crate) fn print_event(&mut self, event: &Event) {
+        match (self.format, event) {
+            (FormatSetting::Json, Event::TestStarted { name, xfail }) => writeln!(
+                self.out,
+                r#"{{"type":"test","event":"started","name":"{}","xfail":{}}}"#,
+                escape8259::escape(name),
+                xfail
+            )
+            .unwrap(),
+            (FormatSetting::Json, Event::FixtureSetupStarted { test, fixture }) => writeln!(
+                self.out,
+                r#"{{"type":"fixture","event":"setup_started","test":"{}","fixture":"{}"}}"#,
+                escape8259::escape(test),
+                escape8259::escape(fixture),
+            )
+            .unwrap(),
+            (
+                FormatSetting::Json,
+                Event::FixtureSetupFinished {
+                    test,
+                    fixture,
+                    duration,
+                    error,
+                },
+            ) => writeln!(
+                self.out,
+                concat!(
+                    r#"{{"type":"fixture","event":"setup_finished","test":"{}","fixture":"{}","#,
+                    r#""duration":{},"error":{}}}"#
+                ),
+                escape8259::escape(test),
+                escape8259::escape(fixture),
+                duration.as_secs_f64(),
+                json_string(error.as_deref()),
+            )
+            .unwrap(),
+            (_, Event::TestStarted { name, xfail }) => {
+                if !self.threaded {
+                    let kind = if *xfail { "XFAIL" } else { "" };
+                    self.print_test(name, kind);
+                }
+            }
+            (_, Event::FixtureSetupStarted { .. } | Event::FixtureSetupFinished { .. }) => {}
+            (_, Event::TestFinished(report)) => {
+                if self.threaded {
+                    let kind = if report.xfail { "XFAIL" } else { "" };
+                    self.print_test(&report.name, kind);
+                }
+                self.print_single_outcome(report);
+            }
+        }
+    }
+
+    /// Prints the outcome of a single tests. `ok` or `FAILED` in pretty mode
+    /// and `.` or `F` in terse mode.
+    pub(crate) fn print_single_outcome(&mut self, report: &TestReport) {
+        let outcome = &report.outcome;
+        match self.format {
+            FormatSetting::Pretty => {
+                self.print_outcome_pretty(outcome);
+                writeln!(self.out).unwrap();
+            }
+            FormatSetting::Terse => {
+                let c = match outcome {
+                    Outcome::Passed | Outcome::XFailed(_) => '.',
+                    Outcome::XPassed { .. } => 'X',
+                    Outcome::Failed(_) => 'F',
+                    Outcome::Error(_) => 'E',
+                    Outcome::TimedOut(_) => 'T',
+                    Outcome::Flaky(_) => 'f',
+                    Outcome::Ignored => 'i',
+                    Outcome::Skipped(_) => 's',
+                };
+
+                let style = color_of_outcome(outcome);
+                write!(self.out, "{style}{}{style:#}", c).unwrap();
+            }
+            FormatSetting::Json => {
+                let failure = match outcome {
+                    Outcome::Failed(msg)
+                    | Outcome::Error(msg)
+                    | Outcome::XFailed(msg)
+                    | Outcome::TimedOut(msg)
+                    | Outcome::Flaky(msg)
+                    | Outcome::Skipped(msg) => Some(msg.clone()),
+                    Outcome::XPassed { .. } => outcome.failure(),
+                    _ => None,
+                };
+                writeln!(
+                    self.out,
+                    concat!(
+                        r#"{{"type":"test","event":"finished","name":"{}","combination":{},"#,
+                        r#""xfail":{},"ignore":{},"outcome":"{}","duration":{},"failure":{},"output":{}}}"#
+                    ),
+                    escape8259::escape(&report.name),
+                    json_string(report.combination.as_deref()),
+                    report.xfail,
+                    report.ignore,
+                    match outcome {
+                        Outcome::Passed => "passed",
+                        Outcome::XFailed(_) => "xfailed",
+                        Outcome::XPassed { .. } => "xpassed",
+                        Outcome::Failed(_) => "failed",
+                        Outcome::Error(_) => "error",
+                        Outcome::TimedOut(_) => "timeout",
+                        Outcome::Flaky(_) => "flaky",
+                        Outcome::Ignored => "ignored",
+                        Outcome::Skipped(_) => "skipped",
+                    },
+                    report.duration.as_secs_f64(),
+                    json_string(failure.as_deref()),
+                    json_string(Some(&report.output).filter(|o| !o.is_empty()).map(String::as_str)),
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    /// Prints the summary line after all tests have been executed.
+    pub(crate) fn print_summary(&mut self, conclusion: &Conclusion) {
+        match self.format {
+            FormatSetting::Pretty | FormatSetting::Terse => {
+                let outcome = if conclusion.has_failed() {
+                    Outcome::Failed(String::new())
+                } else {
+                    Outcome::Passed
+                };
+
+                writeln!(self.out).unwrap();
+                let plural = |n| if n == 1 { "" } else { "s" };
+                if let Some(signal) = conclusion.interrupted {
+                    writeln!(
+                        self.out,
+                        "interrupted by signal {signal}, {} test{} not run",
+                        conclusion.num_not_run,
+                        plural(conclusion.num_not_run),
+                    )
+                    .unwrap();
+                } else if conclusion.num_not_run > 0 {
+                    writeln!(
+                        self.out,
+                        "stopped after {} failure{}, {} test{} not run",
+                        conclusion.num_failures(),
+                        plural(conclusion.num_failures()),
+                        conclusion.num_not_run,
+                        plural(conclusion.num_not_run),
+                    )
+                    .unwrap();
+                }
+                if let Some(seed) = conclusion.seed {
+                    writeln!(
+                        self.out,
+                        "tests have been shuffled with seed {seed} (rerun with `--seed {seed}`)"
+                    )
+                    .unwrap();
+                }
+                write!(self.out, "test result: ").unwrap();
+                if conclusion.no_tests_collected() {
+                    let style = Style::new().fg_color(Some(AnsiColor::Yellow.into()));
+                    write!(self.out, "{style}no tests collected{style:#}").unwrap();
+                } else {
+                    self.print_outcome_pretty(&outcome);
+                }
+                write!(self.out, ". {} passed; ", conclusion.num_passed).unwrap();
+                if conclusion.num_flaky > 0 {
+                    write!(self.out, "{} flaky; ", conclusion.num_flaky).unwrap();
+                }
+                if conclusion.num_xpassed > 0 {
+                    write!(self.out, "{} xpassed; ", conclusion.num_xpassed).unwrap();
+                }
+                if conclusion.num_skipped > 0 {
+                    write!(self.out, "{} skipped; ", conclusion.num_skipped).unwrap();
+                }
+                write!(self.out, "{} failed; ", conclusion.num_failed).unwrap();
+                if conclusion.num_errors > 0 {
+                    write!(
+                        self.out,
+                        "{} error{}; ",
+                        conclusion.num_errors,
+                        plural(conclusion.num_errors)
+                    )
+                    .unwrap();
+                }
+                writeln!(
+                    self.out,
+                    "{} ignored; {} measured; {} filtered out; finished in {:.2}s",
+                    conclusion.num_ignored,
+                    conclusion.num_measured,
+                    conclusion.num_filtered_out,
+                    conclusion.duration.as_secs_f64()
+                )
+                .unwrap();
+                if !conclusion.session_errors.is_empty() {
+                    writeln!(self.out).unwrap();
+                    writeln!(self.out, "session errors:").unwrap();
+                    for error in &conclusion.session_errors {
+                        writeln!(self.out, "    {}", error.replace('\n', "\n    ")).unwrap();
+                    }
+                }
+                writeln!(self.out).unwrap();
+            }
+            FormatSetting::Json => {
+                writeln!(
+                    self.out,
+                    concat!(
+                        r#"{{"type":"run","event":"finished","outcome":"{}","passed":{},"flaky":{},"failed":{},"errors":{},"xpassed":{},"#,
+                        r#""ignored":{},"skipped":{},"measured":{},"filtered_out":{},"not_run":{},"duration":{},"seed":{},"#,
+                        r#""session_errors":[{}]}}"#,
+                    ),
+                    if conclusion.interrupted.is_some() {
+                        "interrupted"
+                    } else if conclusion.num_failed > 0 {
+                        "failed"
+                    } else if conclusion.has_errors() {
+                        "error"
+                    } else if conclusion.no_tests_collected() {
+                        "no_tests"
+                    } else {
+                        "ok"
+                    },
+                    conclusion.num_passed,
+                    conclusion.num_flaky,
+                    conclusion.num_failed,
+                    conclusion.num_errors,
+                    conclusion.num_xpassed,
+                    conclusion.num_ignored,
+                    conclusion.num_skipped,
+                    conclusion.num_measured,
+                    conclusion.num_filtered_out,
+                    conclusion.num_not_run,
+                    conclusion.duration.as_secs_f64(),
+                    match conclusion.seed {
+                        Some(seed) => seed.to_string(),
+                        None => "null".to_owned(),
+                    },
+                    conclusion
+                        .session_errors
+                        .iter()
+                        .map(|e| json_string(Some(e)))
+                        .collect::<Vec<_>>()
+                        .join(","),
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    /// Prints a list of all tests. Used if `--list` is set.
+    pub(crate) fn print_list(&mut self, tests: &[Test], ignored: bool) {
+        for test in tests {
+            // libtest prints out:
+            // * all tests without `--ignored`
+            // * just the ignored tests with `--ignored`
+            if ignored && !test.is_ignored() {
+                continue;
+            }
+
+            let kind = if test.kind().is_empty() {
+                String::new()
+            } else {
+                format!("[{}] ", test.kind())
+            };
+
+            writeln!(self.out, "{}{}: test", kind, test.name()).unwrap();
+        }
+    }
+
+    /// Prints the fixture instances each test would set up, and where the fan-out of the
+    /// parametrized tests comes from.
+    pub(crate) fn print_setup_plan(&mut self, tests: &[Test]) {
+        let shared = SharedInstances::new(tests);
+        for test in tests {
+            writeln!(self.out, "{}", test.name()).unwrap();
+            for fixture in test.plan() {
+                self.print_fixture_plan(fixture, &shared, 1);
+            }
+        }
+
+        let fan_out = plan::fan_out(tests);
+        if !fan_out.is_empty() {
+            writeln!(self.out).unwrap();
+            writeln!(self.out, "fan-out:").unwrap();
+        }
+        for plan::FanOut {
+            function,
+            num_cases,
+            fixtures,
+        } in fan_out
+        {
+            let fixtures = fixtures
+                .iter()
+                .map(|(fixture, count)| format!("{fixture} ({count})"))
+                .collect::<Vec<_>>();
+            writeln!(
+                self.out,
+                "    {function}: {num_cases} cases from {}",
+                fixtures.join(" x ")
+            )
+            .unwrap();
+        }
+
+        writeln!(self.out).unwrap();
+        writeln!(
+            self.out,
+            "{} tests, {} shared fixture instance(s)",
+            tests.len(),
+            shared.len()
+        )
+        .unwrap();
+    }
+
+    fn print_fixture_plan(&mut self, plan: &FixturePlan, shared: &SharedInstances, depth: usize) {
+        let indent = "    ".repeat(depth);
+        write!(
+            self.out,
+            "{indent}{} [{}]",
+            plan.label(),
+            plan.scope.as_str()
+        )
+        .unwrap();
+        match plan.shared {
+            Some(id) => {
+                let (short, count) = shared.get(id);
+                writeln!(self.out, " #{short}, shared by {count} test(s)").unwrap();
+            }
+            None => writeln!(self.out, " fresh").unwrap(),
+        }
+        for dep in &plan.dependencies {
+            self.print_fixture_plan(dep, shared, depth + 1);
+        }
+    }
+
+    /// Prints the fixtures with their docs, scope, dependencies and parameters.
+    ///
+    /// Fixtures which are not in `used` (the locations of the fixtures set up by the tests) are
+    /// flagged as unused. Fixtures are compared by location as fixtures in different modules may
+    /// have the same name.
+    pub(crate) fn print_fixtures(
+        &mut self,
+        fixtures: &[FixtureInfo],
+        used: &HashSet<(&'static str, u32)>,
+    ) {
+        let unused_style = Style::new().fg_color(Some(Color::Ansi(AnsiColor::Yellow)));
+        for fixture in fixtures {
+            write!(self.out, "{} [{}]", fixture.name, fixture.scope.as_str()).unwrap();
+            if !used.contains(&(fixture.file, fixture.line)) {
+                write!(self.out, " {unused_style}(unused){unused_style:#}").unwrap();
+            }
+            writeln!(self.out, " -- {}:{}", fixture.file, fixture.line).unwrap();
+            if !fixture.dependencies.is_empty() {
+                writeln!(self.out, "    uses: {}", fixture.dependencies.join(", ")).unwrap();
+            }
+            if let Some(params) = fixture.params {
+                writeln!(self.out, "    params: {}", params().join(", ")).unwrap();
+            }
+            for line in fixture.doc.lines() {
+                if line.is_empty() {
+                    writeln!(self.out).unwrap();
+                } else {
+                    writeln!(self.out, "    {line}").unwrap();
+                }
+            }
+            writeln!(self.out).unwrap();
+        }
+        writeln!(self.out, "{} fixtures", fixtures.len()).unwrap();
+    }
+
+    /// Prints the captured output of the successful tests. Does nothing unless `--show-output`
+    /// is set.
+    pub(crate) fn print_successes(&mut self, conclusion: &Conclusion) {
+        if !self.show_output || self.format == FormatSetting::Json {
+            return;
+        }
+        let successes = conclusion
+            .reports
+            .iter()
+            .filter(|r| {
+                r.outcome.failure().is_none()
+                    && !matches!(r.outcome, Outcome::Ignored | Outcome::Skipped(_))
+            })
+            .collect::<Vec<_>>();
+        if successes.is_empty() {
+            return;
+        }
+
+        writeln!(self.out).unwrap();
+        writeln!(self.out, "successes:").unwrap();
+        writeln!(self.out).unwrap();
+
+        for report in &successes {
+            if !report.output.is_empty() {
+                writeln!(self.out, "---- {} stdout ----", report.name).unwrap();
+                write!(self.out, "{}", report.output).unwrap();
+                writeln!(self.out).unwrap();
+            }
+        }
+
+        writeln!(self.out).unwrap();
+        writeln!(self.out, "successes:").unwrap();
+        for report in &successes {
+            writeln!(self.out, "    {}", report.name).unwrap();
+        }
+    }
+
+    /// Prints a list of failed tests with their messages (and captured output). This is only
+    /// called if there were any failures.
+    pub(crate) fn print_failures(&mut self, conclusion: &Conclusion) {
+        if self.format == FormatSetting::Json {
+            return;
+        }
+        let fails = conclusion
+            .reports
+            .iter()
+            .filter_map(|r| Some((&r.name, r.outcome.failure()?, &r.output)))
+            .collect::<Vec<_>>();
+
+        writeln!(self.out).unwrap();
+        writeln!(self.out, "failures:").unwrap();
+        writeln!(self.out).unwrap();
+
+        for (name, msg, output) in &fails {
+            if !output.is_empty() {
+                writeln!(self.out, "---- {} stdout ----", name).unwrap();
+                write!(self.out, "{}", output).unwrap();
+            }
+            // The message of a panic is already in the output (written by the panic hook).
+            if !output.contains(msg.as_str()) {
+                writeln!(self.out, "---- {} ----", name).unwrap();
+                writeln!(self.out, "{}", msg).unwrap();
+            }
+            writeln!(self.out).unwrap();
+        }
+
+        writeln!(self.out).unwrap();
+        writeln!(self.out, "failures:").unwrap();
+        for (name, _, _) in &fails {
+            writeln!(self.out, "    {}", name).unwrap();
+        }
+    }
+
+    /// Prints the `count` slowest tests (all of them if `count` is 0), with the time spent in
+    /// each phase, and the setup and teardown times of the global fixtures.
+    pub(crate) fn print_durations(&mut self, conclusion: &Conclusion, count: usize) {
+        if self.format == FormatSetting::Json {
+            return;
+        }
+        let mut reports = conclusion
+            .reports
+            .iter()
+            .filter(|r| !matches!(r.outcome, Outcome::Ignored | Outcome::Skipped(_)))
+            .collect::<Vec<_>>();
+        reports.sort_by_key(|r| std::cmp::Reverse(r.duration));
+        if count > 0 {
+            reports.truncate(count);
+        }
+
+        writeln!(self.out).unwrap();
+        writeln!(
+            self.out,
+            "slowest {} durations (setup / call / teardown):",
+            reports.len()
+        )
+        .unwrap();
+        for report in reports {
+            let phases = report.phases;
+            writeln!(
+                self.out,
+                "    {:.3}s ({:.3}s / {:.3}s / {:.3}s) {}",
+                report.duration.as_secs_f64(),
+                phases.setup.as_secs_f64(),
+                phases.call.as_secs_f64(),
+                phases.teardown.as_secs_f64(),
+                report.name
+            )
+            .unwrap();
+        }
+
+        if conclusion.global_fixtures.is_empty() {
+            return;
+        }
+        writeln!(self.out).unwrap();
+        writeln!(self.out, "global fixtures (setup / teardown):").unwrap();
+        for fixture in &conclusion.global_fixtures {
+            let teardown = match fixture.teardown {
+                Some(teardown) => format!("{:.3}s", teardown.as_secs_f64()),
+                None => "-".to_owned(),
+            };
+            writeln!(
+                self.out,
+                "    {:.3}s / {teardown} {}",
+                fixture.setup.as_secs_f64(),
+                fixture.name
+            )
+            .unwrap();
+        }
+    }
+
+    /// Prints a colored 'ok'/'FAILED'/'ignored'.
+    fn print_outcome_pretty(&mut self, outcome: &Outcome) {
+        let style = color_of_outcome(outcome);
+        let s = match outcome {
+            Outcome::Passed | Outcome::XFailed(_) => "ok",
+            Outcome::XPassed { .. } => "XPASS",
+            Outcome::Failed(_) => "FAILED",
+            Outcome::Error(_) => "ERROR",
+            Outcome::TimedOut(_) => "TIMEOUT",
+            Outcome::Flaky(_) => "FLAKY",
+            Outcome::Ignored => "ignored",
+            Outcome::Skipped(_) => "SKIPPED",
+        };
+
+        write!(self.out, "{style}{s}{style:#}").unwrap();
+        if let Outcome::Skipped(reason) | Outcome::XPassed { reason, .. } = outcome
+            && !reason.is_empty()
+        {
+            write!(self.out, " ({reason})").unwrap();
+        }
+    }
+}
+
+/// Formats an optional string as a json value (`null` if `None`).
+fn json_string(s: Option<&str>) -> String {
+    match s {
+        Some(s) => format!("\"{}\"", escape8259::escape(s)),
+        None => "null".to_owned(),
+    }
+}
+
+/// Returns the style associated with the given outcome.
+fn color_of_outcome(outcome: &Outcome) -> Style {
+    let color = match outcome {
+        Outcome::Passed | Outcome::XFailed(_) => AnsiColor::Green,
+        Outcome::Failed(_)
+        | Outcome::Error(_)
+        | Outcome::TimedOut(_)
+        | Outcome::XPassed { strict: true, .. } => AnsiColor::Red,
+        Outcome::XPassed { strict: false, .. } => AnsiColor::Yellow,
+        Outcome::Ignored | Outcome::Skipped(_) | Outcome::Flaky(_) => AnsiColor::Yellow,
+    };
+    Style::new().fg_color(Some(Color::Ansi(color)))
+}
@@ -2,6 +2,7 @@ use std::{cmp::PartialEq, sync::Arc};
 
 use super::{
     fixture::{FixtureCreationResult, FixtureProxy},
+    plan::FixturePlan,
     test::TestContext,
     test_name::TestName,
 };
@@ -25,6 +26,9 @@ pub trait ProxyCall<Args> {
     fn call<F, Output>(self, f: F) -> FixtureCreationResult<Output>
     where
         F: FnOnce(CallArgs<Args>) -> FixtureCreationResult<Output>;
+
+    /// The fixture instances the proxies would build.
+    fn plan(&self) -> Vec<FixturePlan>;
 }
 
 macro_rules! impl_fixture_combination_call {
@@ -40,6 +44,10 @@ macro_rules! impl_fixture_combination_call {
             {
                 f(CallArgs(()))
             }
+
+            fn plan(&self) -> Vec<FixturePlan> {
+                Vec::new()
+            }
         }
     };
     (($($types:tt),+), ($($names:ident),+)) => {
@@ -58,6 +66,11 @@ macro_rules! impl_fixture_combination_call {
                 let call_args = CallArgs(($($names.build()?),+,));
                 f(call_args)
             }
+
+            fn plan(&self) -> Vec<FixturePlan> {
+                let ($($names),+, ) = &self.0;
+                vec![$($names.plan()),+]
+            }
         }
     }
 }
@@ -279,11 +292,11 @@ macro_rules! iter_proxy {
 
 macro_rules! impl_fixture_call {
     (@proxy_setup, $proxy_matrix:expr, $ctx:expr, $proxy:ident) => {{
-        let proxy_matrix = $proxy_matrix.feed($proxy::setup($ctx));
+        let proxy_matrix = $proxy_matrix.feed($ctx.get_fixture::<$proxy>());
         proxy_matrix.flatten()
     }};
     (@proxy_setup, $proxy_matrix:expr, $ctx:expr, $proxy:ident, $($types:tt),+) => {{
-        let proxy_matrix = $proxy_matrix.feed($proxy::setup($ctx));
+        let proxy_matrix = $proxy_matrix.feed($ctx.get_fixture::<$proxy>());
         impl_fixture_call!(@proxy_setup, proxy_matrix, $ctx, $($types),+)
     }};
 
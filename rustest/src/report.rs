@@ -0,0 +1,276 @@
+use std::{path::Path, process::ExitCode, time::Duration};
+
+/// Exit code when tests have failed (as libtest).
+pub(crate) const EXIT_FAILED: u8 = 101;
+
+/// Exit code when no test has failed, but there were fixture errors.
+pub(crate) const EXIT_ERROR: u8 = 102;
+
+/// Exit code when no test has been collected (as pytest).
+pub(crate) const EXIT_NO_TESTS: u8 = 5;
+
+/// The outcome of a test run.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    /// The test passed.
+    Passed,
+
+    /// The test failed, as expected (test is marked as `xfail`).
+    ///
+    /// Contains the failure message.
+    XFailed(String),
+
+    /// The test passed, but it is marked as `xfail` (XPASS).
+    ///
+    /// This is a failure if the xfail is strict.
+    XPassed {
+        /// Why the test was expected to fail (may be empty).
+        reason: String,
+        strict: bool,
+    },
+
+    /// The test failed.
+    ///
+    /// Contains the failure message.
+    Failed(String),
+
+    /// A fixture of the test has failed to set up (or to tear down), so the test could not be
+    /// run properly (ERROR).
+    ///
+    /// Contains the fixture error.
+    Error(String),
+
+    /// The test has not finished in time.
+    ///
+    /// Contains the timeout message (naming where the test was stuck).
+    TimedOut(String),
+
+    /// The test failed but passed when retried.
+    ///
+    /// Contains the failure messages of the failed attempts.
+    Flaky(String),
+
+    /// The test has not been run as it is ignored.
+    Ignored,
+
+    /// The test (or one of its fixtures) has been skipped with `skip!`.
+    ///
+    /// Contains the reason of the skip.
+    Skipped(String),
+}
+
+impl Outcome {
+    /// Is the outcome a failure (which may be retried) ?
+    pub(crate) fn is_failure(&self) -> bool {
+        matches!(self, Outcome::Failed(_) | Outcome::TimedOut(_))
+    }
+
+    /// The failure message, if the outcome fails the run.
+    pub(crate) fn failure(&self) -> Option<String> {
+        match self {
+            Outcome::Failed(msg) | Outcome::Error(msg) | Outcome::TimedOut(msg) => {
+                Some(msg.clone())
+            }
+            Outcome::XPassed {
+                reason,
+                strict: true,
+            } if reason.is_empty() => Some("test passed, but was expected to fail".to_owned()),
+            Outcome::XPassed {
+                reason,
+                strict: true,
+            } => Some(format!("test passed, but was expected to fail: {reason}")),
+            _ => None,
+        }
+    }
+}
+
+/// The time spent in each phase of a test.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Phases {
+    /// Setup of the fixtures (global fixtures excluded).
+    pub setup: Duration,
+
+    /// The test itself.
+    pub call: Duration,
+
+    /// Teardown of the fixtures (global fixtures excluded).
+    pub teardown: Duration,
+}
+
+impl Phases {
+    pub(crate) fn add(&mut self, other: Phases) {
+        self.setup += other.setup;
+        self.call += other.call;
+        self.teardown += other.teardown;
+    }
+}
+
+/// The time spent to setup and teardown a global fixture.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlobalFixtureReport {
+    /// The name of the fixture.
+    pub name: String,
+
+    /// The time spent to setup the fixture.
+    pub setup: Duration,
+
+    /// The time spent to teardown the fixture (if it has been torn down).
+    pub teardown: Option<Duration>,
+}
+
+/// The report of a test, once it has been run.
+#[derive(Debug, Clone)]
+pub struct TestReport {
+    /// The name of the test (including the fixture combination).
+    pub name: String,
+
+    /// The name of the fixture combination (if any).
+    pub combination: Option<String>,
+
+    /// Is the test expected to fail ?
+    pub xfail: bool,
+
+    /// Is the test marked as ignored ?
+    pub ignore: bool,
+
+    /// The outcome of the test.
+    pub outcome: Outcome,
+
+    /// The time spent to run the test (including fixture setup).
+    pub duration: Duration,
+
+    /// The time spent in each phase of the test (of all its attempts).
+    pub phases: Phases,
+
+    /// The output (stdout and stderr) of the test, empty if output is not captured.
+    pub output: String,
+}
+
+/// The conclusion of a test run.
+#[derive(Debug, Clone, Default)]
+pub struct Conclusion {
+    /// Tests not run as they are filtered out.
+    pub num_filtered_out: u64,
+
+    /// Tests which have passed (or failed as expected).
+    pub num_passed: u64,
+
+    /// Tests which have failed (or timed out, or passed while expected to fail with a strict xfail).
+    pub num_failed: u64,
+
+    /// Tests which could not be run properly because of a fixture error (ERROR).
+    pub num_errors: u64,
+
+    /// Tests which have passed while expected to fail (with a non strict xfail).
+    pub num_xpassed: u64,
+
+    /// Tests which have passed only when retried.
+    pub num_flaky: u64,
+
+    /// Tests not run as they are ignored.
+    pub num_ignored: u64,
+
+    /// Tests skipped at runtime (by themselves or their fixtures).
+    pub num_skipped: u64,
+
+    /// Benchmarks run (always 0 as rustest does not run benchmarks).
+    pub num_measured: u64,
+
+    /// Tests not run because of too many failures (`--maxfail`) or an interruption.
+    pub num_not_run: u64,
+
+    /// The reports of the tests, in the order they have finished.
+    pub reports: Vec<TestReport>,
+
+    /// The time spent to run all the tests.
+    pub duration: Duration,
+
+    /// The seed used to shuffle the tests (if they have been shuffled).
+    pub seed: Option<u64>,
+
+    /// The global fixtures set up during the run.
+    pub global_fixtures: Vec<GlobalFixtureReport>,
+
+    /// The signal which has interrupted the run (if any).
+    pub interrupted: Option<i32>,
+
+    /// Errors which are not part of a test (teardown of global fixtures).
+    pub session_errors: Vec<String>,
+}
+
+impl Conclusion {
+    pub(crate) fn add(&mut self, report: TestReport) {
+        match report.outcome {
+            Outcome::Passed | Outcome::XFailed(_) => self.num_passed += 1,
+            Outcome::Failed(_) | Outcome::TimedOut(_) => self.num_failed += 1,
+            Outcome::XPassed { strict: true, .. } => self.num_failed += 1,
+            Outcome::Error(_) => self.num_errors += 1,
+            Outcome::XPassed { strict: false, .. } => self.num_xpassed += 1,
+            Outcome::Flaky(_) => self.num_flaky += 1,
+            Outcome::Ignored => self.num_ignored += 1,
+            Outcome::Skipped(_) => self.num_skipped += 1,
+        }
+        self.reports.push(report);
+    }
+
+    /// Has any test failed (or errored), or has the session errored ?
+    pub fn has_failed(&self) -> bool {
+        self.num_failed > 0 || self.has_errors()
+    }
+
+    /// Has any test errored, or has the session errored ?
+    pub(crate) fn has_errors(&self) -> bool {
+        self.num_errors > 0 || !self.session_errors.is_empty()
+    }
+
+    /// The number of tests which have failed or errored.
+    pub(crate) fn num_failures(&self) -> u64 {
+        self.num_failed + self.num_errors
+    }
+
+    /// Has no test been collected ?
+    ///
+    /// Tests filtered out are collected, so filtering all the tests of a binary, as
+    /// `cargo test <filter>` does, is not the same.
+    pub(crate) fn no_tests_collected(&self) -> bool {
+        self.reports.is_empty() && self.num_not_run == 0 && self.num_filtered_out == 0
+    }
+
+    /// The exit code of the test binary.
+    ///
+    /// * 128 + signal if the run has been interrupted,
+    /// * [EXIT_FAILED] if a test has failed,
+    /// * [EXIT_ERROR] if no test has failed but some had fixture errors (or a global fixture has
+    ///   failed to teardown),
+    /// * [EXIT_NO_TESTS] if [no test has been collected](Self::no_tests_collected),
+    /// * 0 otherwise.
+    pub(crate) fn exit_code(&self) -> ExitCode {
+        if let Some(signal) = self.interrupted {
+            ExitCode::from((128 + signal) as u8)
+        } else if self.num_failed > 0 {
+            ExitCode::from(EXIT_FAILED)
+        } else if self.has_errors() {
+            ExitCode::from(EXIT_ERROR)
+        } else if self.no_tests_collected() {
+            ExitCode::from(EXIT_NO_TESTS)
+        } else {
+            ExitCode::SUCCESS
+        }
+    }
+}
+
+/// The name of the test suite, ie the name of the test binary (without cargo's hash suffix).
+pub(crate) fn suite_name() -> String {
+    let name = std::env::args_os()
+        .next()
+        .as_deref()
+        .and_then(|p| Path::new(p).file_stem())
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "rustest".to_owned());
+    match name.rsplit_once('-') {
+        Some((base, hash)) if hash.len() == 16 && hash.chars().all(|c| c.is_ascii_hexdigit()) => {
+            base.to_owned()
+        }
+        _ => name,
+    }
+}
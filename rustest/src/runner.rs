@@ -0,0 +1,455 @@
+//! The test runner, used instead of the one of libtest-mimic (which only parses the command line).
+//!
+//! Tests are run by worker threads (`--test-threads`), the main thread handles interruptions.
+//! A test reports [Event]s (start, fixture setups, end) to an [EventSink], which prints them with
+//! the [Printer] and gathers the [TestReport]s into the [Conclusion] of the run. The conclusion is
+//! used for the summary, the exit code, the JUnit report and the cache of `--lf`.
+
+use std::{
+    sync::{
+        Arc, Mutex, PoisonError,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, RecvTimeoutError},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use super::{
+    args::Arguments,
+    cache::Cache,
+    capture::Capture,
+    event::{self, Event, EventSink, FixtureStack},
+    fixture::FixtureRegistry,
+    isolate, junit, plan, plugin,
+    printer::Printer,
+    report::{Conclusion, Outcome, Phases, TestReport},
+    signal,
+    test::{Test, panic_message},
+};
+
+/// How long running tests are waited for once the run is interrupted.
+const INTERRUPT_GRACE: Duration = Duration::from_secs(5);
+
+/// Wait for the workers running the tests to finish (ie, to hang up `done`).
+///
+/// If the run is interrupted and tests are still running after [INTERRUPT_GRACE], global
+/// fixtures are torn down and the process exits.
+fn wait_workers(
+    done: &mpsc::Receiver<std::convert::Infallible>,
+    capture: Option<&Capture>,
+    pending: &Mutex<std::vec::IntoIter<Test>>,
+    global_registry: &Mutex<FixtureRegistry>,
+) {
+    let mut deadline = None;
+    loop {
+        let timeout = match deadline {
+            Some(deadline) => deadline - Instant::now().min(deadline),
+            // Signals cannot wake us up, so we have to poll.
+            None => Duration::from_millis(50),
+        };
+        match done.recv_timeout(timeout) {
+            Err(RecvTimeoutError::Disconnected) => return,
+            Err(RecvTimeoutError::Timeout) => {}
+        }
+        match (deadline, signal::received()) {
+            (None, Some(_)) => {
+                deadline = Some(Instant::now() + INTERRUPT_GRACE);
+            }
+            (Some(deadline), Some(signal)) if Instant::now() >= deadline => {
+                if let Some(capture) = capture {
+                    capture.restore();
+                }
+                eprintln!(
+                    "Tests still running {:.0}s after interruption, tearing down global fixtures and exiting",
+                    INTERRUPT_GRACE.as_secs_f64()
+                );
+                // Tests not run yet hold the proxies of the global fixtures too.
+                drop(std::mem::take(
+                    &mut *pending.lock().unwrap_or_else(PoisonError::into_inner),
+                ));
+                tear_down_globals(global_registry);
+                std::process::exit(128 + signal);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Tear down the global fixtures (which are not held by tests anymore).
+fn tear_down_globals(global_registry: &Mutex<FixtureRegistry>) {
+    drop(std::mem::take(
+        &mut *global_registry
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner),
+    ));
+}
+
+/// Receive the events of a run, print them and gather the reports.
+struct Reporter {
+    printer: Printer,
+    conclusion: Conclusion,
+}
+
+impl Reporter {
+    fn handle(&mut self, event: Event) {
+        self.printer.print_event(&event);
+        if let Event::TestFinished(report) = event {
+            self.conclusion.add(report);
+        }
+    }
+}
+
+/// Run a test, catching its panics.
+pub(crate) fn run_test(
+    test: Test,
+    name: &str,
+    sink: &EventSink,
+    fixtures: &FixtureStack,
+) -> (Outcome, Phases) {
+    event::with_test(name, sink, fixtures, || {
+        // Test already catches panics of the test itself, but fixture setup may panic too.
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(move || test.run())).unwrap_or_else(
+            |cause| {
+                let outcome = match panic_message(&*cause) {
+                    Some(payload) => Outcome::Failed(format!("test panicked: {payload}")),
+                    None => Outcome::Failed("test panicked".to_owned()),
+                };
+                (outcome, Phases::default())
+            },
+        )
+    })
+}
+
+/// The outcome of the test `name` which has timed out while setting up `fixture` (or in its body).
+pub(crate) fn timed_out_outcome(name: &str, timeout: Duration, fixture: Option<String>) -> Outcome {
+    let stuck_in = match fixture {
+        Some(fixture) => format!("in fixture {fixture}"),
+        None => "in test body".to_owned(),
+    };
+    Outcome::TimedOut(format!(
+        "test {name} timed out after {:.2}s {stuck_in}",
+        timeout.as_secs_f64()
+    ))
+}
+
+/// Run a test in its own thread, giving up on it after `timeout`.
+///
+/// A thread cannot be killed, so a test timing out is left running in the background
+/// (and its events are discarded). Its output cannot be told apart from the output of the next
+/// tests, so `capture` is stopped.
+fn run_test_with_timeout(
+    test: Test,
+    name: &str,
+    sink: &EventSink,
+    timeout: Duration,
+    capture: Option<&Capture>,
+) -> (Outcome, Phases) {
+    let fixtures = FixtureStack::default();
+    let timed_out = Arc::new(AtomicBool::new(false));
+    let (sender, receiver) = mpsc::channel();
+    let thread_sink: EventSink = {
+        let sink = Arc::clone(sink);
+        let timed_out = Arc::clone(&timed_out);
+        Arc::new(move |event| {
+            if !timed_out.load(Ordering::Relaxed) {
+                sink(event)
+            }
+        })
+    };
+    let thread_fixtures = fixtures.clone();
+    let thread_name = name.to_owned();
+    thread::Builder::new()
+        .name(name.to_owned())
+        .spawn(move || {
+            let result = run_test(test, &thread_name, &thread_sink, &thread_fixtures);
+            // Receiver hangs up if the test has timed out, nobody cares about the outcome anymore.
+            let _ = sender.send(result);
+        })
+        .expect("Failed to spawn test thread");
+
+    match receiver.recv_timeout(timeout) {
+        Ok(result) => result,
+        Err(RecvTimeoutError::Timeout) => {
+            timed_out.store(true, Ordering::Relaxed);
+            if let Some(capture) = capture {
+                capture.stop();
+                eprintln!(
+                    "test {name} has timed out and is still running, output is not captured anymore"
+                );
+            }
+            let outcome = timed_out_outcome(name, timeout, fixtures.last());
+            let phases = Phases {
+                call: timeout,
+                ..Phases::default()
+            };
+            (outcome, phases)
+        }
+        Err(RecvTimeoutError::Disconnected) => (
+            Outcome::Failed("test thread exited without reporting".to_owned()),
+            Phases::default(),
+        ),
+    }
+}
+
+/// Run an attempt of a test.
+fn run_attempt(
+    test: Test,
+    args: &Arguments,
+    name: &str,
+    sink: &EventSink,
+    capture: Option<&Capture>,
+) -> (Outcome, Phases) {
+    let timeout = args.timeout_of(&test);
+    let isolated = args.isolate || test.is_isolated();
+    match timeout {
+        _ if isolated => {
+            // Phases are not reported by the child process.
+            let start = Instant::now();
+            let outcome = isolate::run_test(test, timeout);
+            let phases = Phases {
+                call: start.elapsed(),
+                ..Phases::default()
+            };
+            (outcome, phases)
+        }
+        None => run_test(test, name, sink, &FixtureStack::default()),
+        Some(timeout) => run_test_with_timeout(test, name, sink, timeout, capture),
+    }
+}
+
+/// Run a test (and its retries if it fails) and report its outcome to `sink`.
+fn run_single(mut test: Test, args: &Arguments, sink: &EventSink, capture: Option<&Capture>) {
+    let ignored = args.is_ignored(&test);
+    let name = test.name().to_owned();
+    let combination = test.combination().map(str::to_owned);
+    let xfail = test.is_xfail();
+    let ignore = test.is_ignored();
+    for plugin in plugin::plugins() {
+        plugin.on_test_start(&test);
+    }
+    sink(Event::TestStarted {
+        name: name.clone(),
+        xfail,
+    });
+    // Tests are run one at a time while their output is captured.
+    let capture_lock = capture.and_then(Capture::lock);
+    let capture = capture.filter(|_| capture_lock.is_some());
+    let start = Instant::now();
+    let output_start = capture.map(Capture::position);
+    let mut phases = Phases::default();
+    let outcome = if ignored {
+        Outcome::Ignored
+    } else {
+        let regenerator = test.take_regenerator();
+        let retries = args.retries_of(&test);
+        let mut failures = Vec::new();
+        let (mut outcome, attempt_phases) = run_attempt(test, args, &name, sink, capture);
+        phases.add(attempt_phases);
+        while outcome.is_failure() && failures.len() < retries {
+            // Retries are built only when needed, with fresh fixtures.
+            let Some(retry) = regenerator.as_ref().and_then(|r| r.generate(&name)) else {
+                outcome = Outcome::Error(format!(
+                    "{}\ncannot retry test {name}: its generator did not build it again",
+                    outcome.failure().unwrap_or_default()
+                ));
+                break;
+            };
+            failures.push(outcome);
+            let (retry_outcome, attempt_phases) = run_attempt(retry, args, &name, sink, capture);
+            outcome = retry_outcome;
+            phases.add(attempt_phases);
+        }
+        if failures.is_empty() || outcome.is_failure() {
+            outcome
+        } else {
+            let mut msg = format!("test passed on attempt {}", failures.len() + 1);
+            for (idx, failure) in failures.iter().enumerate() {
+                if let Outcome::Failed(failure) | Outcome::TimedOut(failure) = failure {
+                    msg += &format!("\nattempt {} failed: {failure}", idx + 1);
+                }
+            }
+            Outcome::Flaky(msg)
+        }
+    };
+    let duration = start.elapsed();
+    let output = match (capture, output_start) {
+        (Some(capture), Some(output_start)) => capture.read(output_start, capture.position()),
+        _ => String::new(),
+    };
+    let report = TestReport {
+        name,
+        combination,
+        xfail,
+        ignore,
+        outcome,
+        duration,
+        phases,
+        output,
+    };
+    for plugin in plugin::plugins() {
+        plugin.on_test_end(&report);
+    }
+    sink(Event::TestFinished(report));
+}
+
+/// Run the tests and print the results.
+///
+/// Global fixtures are torn down (by emptying `global_registry`) once all tests have run.
+/// Returns `None` if the tests are only listed.
+pub(crate) fn run(
+    args: &Arguments,
+    mut tests: Vec<Test>,
+    global_registry: Arc<Mutex<FixtureRegistry>>,
+    package: Option<&str>,
+) -> Option<Conclusion> {
+    let start_instant = Instant::now();
+    let mut conclusion = Conclusion::default();
+
+    let mut cache = Cache::load(package);
+
+    let len_before = tests.len();
+    tests.retain(|test| !args.is_filtered_out(test));
+    // As with pytest, `--lf` runs all the selected tests if none of them has failed.
+    if args.last_failed && tests.iter().any(|test| cache.has_failed(test.name())) {
+        tests.retain(|test| cache.has_failed(test.name()));
+    }
+    conclusion.num_filtered_out = (len_before - tests.len()) as u64;
+
+    conclusion.seed = args.shuffle_seed();
+    if let Some(seed) = conclusion.seed {
+        fastrand::Rng::with_seed(seed).shuffle(&mut tests);
+    }
+    if args.failed_first {
+        // Sort is stable, so failed tests are still run in the declared (or shuffled) order.
+        tests.sort_by_key(|test| !cache.has_failed(test.name()));
+    }
+
+    let num_threads = args
+        .libtest
+        .test_threads
+        .or_else(|| thread::available_parallelism().ok().map(Into::into))
+        .unwrap_or(1);
+
+    if args.libtest.list {
+        Printer::new(args, &tests, false, None).print_list(&tests, args.libtest.ignored);
+        return None;
+    }
+
+    if args.setup_plan || args.setup_plan_dot.is_some() {
+        if args.setup_plan {
+            Printer::new(args, &tests, false, None).print_setup_plan(&tests);
+        }
+        if let Some(path) = &args.setup_plan_dot
+            && let Err(e) = plan::write_dot(path, &tests)
+        {
+            eprintln!("Cannot write setup plan to {}: {e}", path.display());
+        }
+        return None;
+    }
+
+    let capture = if args.libtest.nocapture {
+        None
+    } else {
+        match Capture::start() {
+            Ok(capture) => Some(capture),
+            Err(e) if e.kind() == std::io::ErrorKind::Unsupported => None,
+            Err(e) => {
+                eprintln!("Cannot capture output of the tests: {e}");
+                None
+            }
+        }
+    };
+    if capture.is_some() && args.libtest.test_threads.is_some_and(|threads| threads > 1) {
+        eprintln!(
+            "Tests are run one at a time while their output is captured, use --nocapture to run them on several threads"
+        );
+    }
+    let stdout = capture.as_ref().and_then(|c| c.stdout().ok());
+    let mut printer = Printer::new(args, &tests, num_threads > 1, stdout);
+
+    printer.print_title(tests.len(), conclusion.num_filtered_out);
+
+    let reporter = Arc::new(Mutex::new(Reporter {
+        printer,
+        conclusion,
+    }));
+    let sink: EventSink = {
+        let reporter = Arc::clone(&reporter);
+        Arc::new(move |event| reporter.lock().unwrap().handle(event))
+    };
+
+    // Stop starting new tests once we have too many failures or the run is interrupted.
+    // Running tests are finished.
+    let max_fail = args.max_fail();
+    let iter = Mutex::new(tests.into_iter());
+    let next_test = || {
+        let stop = signal::received().is_some()
+            || max_fail.is_some_and(|max_fail| {
+                reporter.lock().unwrap().conclusion.num_failures() >= max_fail
+            });
+        if stop {
+            None
+        } else {
+            iter.lock().unwrap().next()
+        }
+    };
+
+    // Tests are run by workers so the main thread can handle interruptions.
+    thread::scope(|scope| {
+        let (done_sender, done) = mpsc::channel();
+        for _ in 0..num_threads {
+            let done_sender = done_sender.clone();
+            let (next_test, sink, capture) = (&next_test, &sink, capture.as_ref());
+            scope.spawn(move || {
+                while let Some(test) = next_test() {
+                    run_single(test, args, sink, capture);
+                }
+                drop(done_sender);
+            });
+        }
+        drop(done_sender);
+        wait_workers(&done, capture.as_ref(), &iter, &global_registry);
+    });
+    let num_not_run = iter.into_inner().unwrap().len() as u64;
+    drop(capture);
+    tear_down_globals(&global_registry);
+    let session_errors = event::take_session_errors();
+
+    // Tests which have timed out may still be running (and holding the sink), so we cannot
+    // take back the reporter.
+    let mut reporter = reporter.lock().unwrap();
+    reporter.conclusion.num_not_run = num_not_run;
+    let Reporter {
+        printer,
+        conclusion,
+    } = &mut *reporter;
+
+    conclusion.duration = start_instant.elapsed();
+    conclusion.interrupted = signal::received();
+    conclusion.global_fixtures = event::take_global_fixtures();
+    conclusion.session_errors = session_errors;
+
+    printer.print_successes(conclusion);
+    if conclusion.num_failures() > 0 {
+        printer.print_failures(conclusion);
+    }
+    if let Some(count) = args.durations {
+        printer.print_durations(conclusion, count);
+    }
+
+    printer.print_summary(conclusion);
+
+    if let Some(path) = &args.junit
+        && let Err(e) = junit::write_report(path, conclusion)
+    {
+        eprintln!("Cannot write junit report to {}: {e}", path.display());
+    }
+
+    cache.update(conclusion);
+    if let Err(e) = cache.save() {
+        eprintln!("Cannot write rustest cache: {e}");
+    }
+
+    Some(std::mem::take(conclusion))
+}
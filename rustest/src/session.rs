@@ -0,0 +1,104 @@
+//! Hooks run around the whole test session, registered from the `#[main]` function.
+
+use super::{plugin::Plugin, report::Conclusion, test::Result};
+
+type StartFn = Box<dyn FnOnce() -> Result>;
+type FinishFn = Box<dyn FnOnce(&Conclusion)>;
+
+/// The test session, given to the function marked with [main](crate::main).
+///
+/// It allows to run code before the tests are collected and once all tests have run (and
+/// global fixtures have been torn down), and to register [Plugin]s.
+///
+/// ```
+/// use rustest::{test, *};
+///
+/// #[test]
+/// fn test_42() {}
+///
+/// #[main]
+/// fn main(session: &mut Session) {
+///     session
+///         .on_start(|| {
+///             // Check prerequisites, initialize logging, ...
+///             Ok(())
+///         })
+///         .on_finish(|conclusion| {
+///             println!("{} tests passed", conclusion.num_passed);
+///         });
+/// }
+/// ```
+#[derive(Default)]
+pub struct Session {
+    on_start: Vec<StartFn>,
+    on_finish: Vec<FinishFn>,
+    plugins: Vec<Box<dyn Plugin>>,
+    package: Option<&'static str>,
+}
+
+impl Session {
+    /// A session without any hook.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run `f` before the tests are collected.
+    ///
+    /// If `f` returns an error, no test is run and the test binary exits with a failure.
+    /// Hooks are run in the order they are registered. They are not run when tests are only
+    /// listed (`--list`, `--fixtures`, `--setup-plan`) nor in the processes running isolated
+    /// tests (`--isolate`).
+    pub fn on_start(&mut self, f: impl FnOnce() -> Result + 'static) -> &mut Self {
+        self.on_start.push(Box::new(f));
+        self
+    }
+
+    /// Run `f` with the conclusion of the run, once all tests have run and global fixtures have
+    /// been torn down.
+    ///
+    /// Hooks are run in the order they are registered, after the summary has been printed.
+    /// Like start hooks, they are not run when tests are only listed.
+    pub fn on_finish(&mut self, f: impl FnOnce(&Conclusion) + 'static) -> &mut Self {
+        self.on_finish.push(Box::new(f));
+        self
+    }
+
+    /// Register a [Plugin] for the run.
+    ///
+    /// Hooks of the plugins are called in the order the plugins are registered.
+    pub fn plugin(&mut self, plugin: impl Plugin + 'static) -> &mut Self {
+        self.plugins.push(Box::new(plugin));
+        self
+    }
+
+    /// Set the name of the package of the test binary. This is called by [main](crate::main).
+    #[doc(hidden)]
+    pub fn set_package(&mut self, package: &'static str) -> &mut Self {
+        self.package = Some(package);
+        self
+    }
+
+    /// The name of the package of the test binary, if known.
+    pub(crate) fn package(&self) -> Option<&'static str> {
+        self.package
+    }
+
+    /// Take the registered plugins.
+    pub(crate) fn take_plugins(&mut self) -> Vec<Box<dyn Plugin>> {
+        std::mem::take(&mut self.plugins)
+    }
+
+    /// Run the start hooks, stopping at the first error.
+    pub(crate) fn start(&mut self) -> Result {
+        std::mem::take(&mut self.on_start)
+            .into_iter()
+            .try_for_each(|f| f())
+    }
+
+    /// Run the finish hooks.
+    pub(crate) fn finish(&mut self, conclusion: &Conclusion) {
+        for f in std::mem::take(&mut self.on_finish) {
+            f(conclusion)
+        }
+    }
+}
@@ -0,0 +1,40 @@
+//! Interruption of a run by SIGINT (Ctrl-C) or SIGTERM.
+//!
+//! On the first signal, no new test is started, running tests are given a short time to finish
+//! and global fixtures are torn down. A second signal exits immediately.
+
+use std::sync::atomic::{AtomicI32, Ordering};
+
+/// The first signal received (0 if none).
+static SIGNAL: AtomicI32 = AtomicI32::new(0);
+
+#[cfg(unix)]
+extern "C" fn handle(signal: libc::c_int) {
+    if SIGNAL.swap(signal, Ordering::SeqCst) != 0 {
+        // SAFETY: `_exit` is async-signal-safe.
+        unsafe { libc::_exit(128 + signal) }
+    }
+}
+
+/// Handle SIGINT and SIGTERM to interrupt the run instead of killing the process.
+pub(crate) fn install() {
+    #[cfg(unix)]
+    for signal in [libc::SIGINT, libc::SIGTERM] {
+        // SAFETY: The handler only touches an atomic (or exits), which is async-signal-safe.
+        unsafe {
+            let mut action: libc::sigaction = std::mem::zeroed();
+            action.sa_sigaction = handle as extern "C" fn(libc::c_int) as libc::sighandler_t;
+            action.sa_flags = libc::SA_RESTART;
+            libc::sigemptyset(&mut action.sa_mask);
+            libc::sigaction(signal, &action, std::ptr::null_mut());
+        }
+    }
+}
+
+/// The signal which has interrupted the run, if any.
+pub(crate) fn received() -> Option<i32> {
+    match SIGNAL.load(Ordering::SeqCst) {
+        0 => None,
+        signal => Some(signal),
+    }
+}
@@ -0,0 +1,79 @@
+//! Skipping a test at runtime with [skip!](crate::skip!).
+//!
+//! Skipping unwinds the test (or fixture setup) with a [Skipped] payload, without calling the
+//! panic hook (so nothing is printed). A fixture skipping is turned into a
+//! [FixtureCreationError] with a [Skipped] error, so tests (and shared fixtures) depending on it
+//! are skipped with the same reason.
+
+use std::any::Any;
+
+use super::fixture::FixtureCreationError;
+
+/// Skip the current test, with a reason.
+///
+/// It can be used in a test body or in a fixture setup. The test is then reported as
+/// `SKIPPED` (and not failed). If a shared fixture skips, all tests using it are skipped with the
+/// same reason.
+///
+/// The reason is formatted as with [format!]:
+///
+/// ```
+/// use rustest::{test, *};
+///
+/// #[fixture(scope=global)]
+/// fn Docker() -> std::path::PathBuf {
+///     let path = std::path::PathBuf::from("/usr/bin/docker");
+///     if !path.exists() {
+///         skip!("docker is not installed at {}", path.display());
+///     }
+///     path
+/// }
+///
+/// #[test]
+/// fn test_docker(docker: Docker) {
+///     assert!(docker.exists());
+/// }
+///
+/// #[main]
+/// fn main() {}
+/// ```
+#[macro_export]
+macro_rules! skip {
+    () => {
+        $crate::raise_skip(::std::string::String::new())
+    };
+    ($($arg:tt)+) => {
+        $crate::raise_skip(::std::format!($($arg)+))
+    };
+}
+
+/// The reason a test or a fixture has been skipped with [skip!](crate::skip!).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Skipped(pub String);
+
+impl std::fmt::Display for Skipped {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "skipped: {}", self.0)
+    }
+}
+
+impl std::error::Error for Skipped {}
+
+/// Skip the current test. This is called by `skip!`, you should not call it directly.
+#[doc(hidden)]
+pub fn raise_skip(reason: String) -> ! {
+    std::panic::resume_unwind(Box::new(Skipped(reason)))
+}
+
+/// The reason of a skip, if `payload` is the payload of a skip.
+pub(crate) fn reason(payload: &(dyn Any + Send)) -> Option<&str> {
+    payload.downcast_ref::<Skipped>().map(|s| s.0.as_str())
+}
+
+impl FixtureCreationError {
+    /// The reason of the skip, if the fixture (or one of its dependencies) has been skipped.
+    pub fn skip_reason(&self) -> Option<&str> {
+        let error: &(dyn std::error::Error + 'static) = &*self.error;
+        error.downcast_ref::<Skipped>().map(|s| s.0.as_str())
+    }
+}
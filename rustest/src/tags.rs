@@ -0,0 +1,162 @@
+//! Selection of tests by tags.
+//!
+//! Tests are selected with a boolean expression over tags, such as `db and not (slow or network)`.
+//! `not` binds tighter than `and`, which binds tighter than `or`.
+
+use std::str::FromStr;
+
+/// A boolean expression over the tags of a test.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum TagExpr {
+    Tag(String),
+    Not(Box<TagExpr>),
+    And(Box<TagExpr>, Box<TagExpr>),
+    Or(Box<TagExpr>, Box<TagExpr>),
+}
+
+impl TagExpr {
+    /// Does a test with `tags` match the expression ?
+    pub(crate) fn matches(&self, tags: &[String]) -> bool {
+        match self {
+            TagExpr::Tag(tag) => tags.contains(tag),
+            TagExpr::Not(expr) => !expr.matches(tags),
+            TagExpr::And(lhs, rhs) => lhs.matches(tags) && rhs.matches(tags),
+            TagExpr::Or(lhs, rhs) => lhs.matches(tags) || rhs.matches(tags),
+        }
+    }
+}
+
+fn tokenize(s: &str) -> Result<Vec<&str>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = s.trim_start();
+    while let Some(c) = rest.chars().next() {
+        let len = match c {
+            '(' | ')' => 1,
+            c if c.is_alphanumeric() || c == '_' => rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len()),
+            c => {
+                return Err(format!(
+                    "Unexpected character `{c}` in tag expression `{s}`"
+                ));
+            }
+        };
+        tokens.push(&rest[..len]);
+        rest = rest[len..].trim_start();
+    }
+    Ok(tokens)
+}
+
+/// A recursive descent parser of tag expressions.
+struct Parser<'a> {
+    tokens: std::iter::Peekable<std::vec::IntoIter<&'a str>>,
+}
+
+impl Parser<'_> {
+    fn or(&mut self) -> Result<TagExpr, String> {
+        let mut expr = self.and()?;
+        while self.tokens.next_if_eq(&"or").is_some() {
+            expr = TagExpr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<TagExpr, String> {
+        let mut expr = self.not()?;
+        while self.tokens.next_if_eq(&"and").is_some() {
+            expr = TagExpr::And(Box::new(expr), Box::new(self.not()?));
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<TagExpr, String> {
+        if self.tokens.next_if_eq(&"not").is_some() {
+            Ok(TagExpr::Not(Box::new(self.not()?)))
+        } else {
+            self.atom()
+        }
+    }
+
+    fn atom(&mut self) -> Result<TagExpr, String> {
+        match self.tokens.next() {
+            Some("(") => {
+                let expr = self.or()?;
+                match self.tokens.next() {
+                    Some(")") => Ok(expr),
+                    Some(token) => Err(format!("Expected `)`, found `{token}`")),
+                    None => Err("Expected `)`, found end of expression".to_owned()),
+                }
+            }
+            Some(token @ (")" | "and" | "or" | "not")) => {
+                Err(format!("Expected a tag, found `{token}`"))
+            }
+            Some(tag) => Ok(TagExpr::Tag(tag.to_owned())),
+            None => Err("Expected a tag, found end of expression".to_owned()),
+        }
+    }
+}
+
+impl FromStr for TagExpr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?.into_iter().peekable(),
+        };
+        let expr = parser.or()?;
+        match parser.tokens.next() {
+            None => Ok(expr),
+            Some(token) => Err(format!("Unexpected `{token}` in tag expression `{s}`")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(expr: &str, tags: &[&str]) -> bool {
+        let tags = tags.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+        expr.parse::<TagExpr>().unwrap().matches(&tags)
+    }
+
+    #[test]
+    fn test_matches() {
+        assert!(matches("db", &["db", "slow"]));
+        assert!(!matches("db", &["slow"]));
+        assert!(matches("db and not slow", &["db"]));
+        assert!(!matches("db and not slow", &["db", "slow"]));
+        assert!(matches("slow or db and network", &["slow"]));
+        assert!(!matches("(slow or db) and network", &["slow"]));
+        assert!(matches("not not db", &["db"]));
+        assert!(matches("not(db)", &[]));
+    }
+
+    #[test]
+    fn test_precedence() {
+        let tag = |t: &str| Box::new(TagExpr::Tag(t.to_owned()));
+        assert_eq!(
+            "a or not b and c".parse(),
+            Ok(TagExpr::Or(
+                tag("a"),
+                Box::new(TagExpr::And(Box::new(TagExpr::Not(tag("b"))), tag("c")))
+            ))
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        for expr in [
+            "",
+            "db and",
+            "(db",
+            "db)",
+            "db slow",
+            "not",
+            "db & slow",
+            "and db",
+        ] {
+            assert!(expr.parse::<TagExpr>().is_err(), "{expr}");
+        }
+    }
+}
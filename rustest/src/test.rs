@@ -1,6 +1,16 @@
-use super::fixture::FixtureProxy;
-use libtest_mimic::Failed;
-use std::{error::Error, fmt::Display};
+use super::{
+    event,
+    fixture::FixtureProxy,
+    plan::FixturePlan,
+    proxy_matrix::Duplicate,
+    report::{Outcome, Phases},
+    skip,
+};
+use std::{
+    error::Error,
+    fmt::Display,
+    time::{Duration, Instant},
+};
 
 /// Result of a test.
 pub type Result = std::result::Result<(), Box<dyn Error>>;
@@ -31,21 +41,18 @@ impl From<googletest::internal::test_outcome::TestFailure> for InnerTestError {
 
 /// The result of test runned by rustest.
 ///
-/// InnerTestResult is necessary as we are somehow between user (`Result`) and libtest_mimic (`LibTestResult`).
+/// InnerTestResult is necessary as we are somehow between user (`Result`) and the runner ([Outcome]).
 /// In the same time, we want to integrate with googletest and it needs us to be `Display`.
 /// User test is returning a Result and is converted to InnerTestResult with IntoError
 /// trait.
 pub type InnerTestResult = std::result::Result<(), InnerTestError>;
 
-/// The result of test runned by libtest_mimic.
-///
-/// LibTestResult is necessary as it is what expected by libtest_mimic.
-/// User test is returning a Result and is converted to InnerTestResult with IntoError
-/// trait.
-pub type LibTestResult = std::result::Result<(), Failed>;
-
-use super::{FixtureCreationResult, FixtureRegistry, FixtureScope};
-use std::any::Any;
+use super::{FixtureCreationResult, FixtureRegistry, FixtureScope, TestGeneratorFn};
+use std::{
+    any::Any,
+    collections::HashSet,
+    sync::{Arc, Mutex, PoisonError},
+};
 
 #[doc(hidden)]
 /// Convert the output of a test into a [InnerTestResult]
@@ -77,12 +84,96 @@ impl<T> IntoError for googletest::Result<T> {
 pub type TestRunner = dyn FnOnce() -> InnerTestResult + 'static;
 pub type TestGenerator = dyn FnOnce() -> FixtureCreationResult<Box<TestRunner>> + Send + 'static;
 
+/// How a test is expected to fail (`#[xfail(...)]`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct XFail {
+    /// Why the test is expected to fail.
+    pub reason: Option<String>,
+
+    /// What the failure message must match.
+    ///
+    /// A test failing with another message is a real failure.
+    pub raises: Option<Raises>,
+
+    /// Does an unexpected pass (`XPASS`) fail the run ?
+    pub strict: bool,
+}
+
+impl Default for XFail {
+    fn default() -> Self {
+        Self {
+            reason: None,
+            raises: None,
+            strict: true,
+        }
+    }
+}
+
+impl XFail {
+    /// Is the failure `msg` the expected one ?
+    pub fn matches(&self, msg: &str) -> bool {
+        self.raises
+            .as_ref()
+            .is_none_or(|raises| raises.matches(msg))
+    }
+}
+
+/// The expected failure of an xfail test: a substring of its message, or a regex matching it.
+#[derive(Debug, Clone)]
+pub struct Raises {
+    pattern: String,
+    /// `None` if the pattern is not a valid regex (it is then only a substring).
+    regex: Option<regex::Regex>,
+}
+
+impl Raises {
+    /// Build the expected failure from its pattern.
+    pub fn new(pattern: impl Into<String>) -> Self {
+        let pattern = pattern.into();
+        let regex = regex::Regex::new(&pattern).ok();
+        Self { pattern, regex }
+    }
+
+    /// The pattern, as given.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Does the failure `msg` contain the pattern, or match it as a regex ?
+    pub fn matches(&self, msg: &str) -> bool {
+        msg.contains(&self.pattern) || self.regex.as_ref().is_some_and(|re| re.is_match(msg))
+    }
+}
+
+impl PartialEq for Raises {
+    fn eq(&self, other: &Self) -> bool {
+        self.pattern == other.pattern
+    }
+}
+
+impl Display for Raises {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.pattern)
+    }
+}
+
 /// An actual test run by rustest
 pub struct Test {
     name: String,
+    combination: Option<String>,
     runner: Box<TestGenerator>,
-    xfail: bool,
+    xfail: Option<XFail>,
+    /// The test must panic (with a message containing the given string, if any).
+    should_panic: Option<Option<String>>,
     ignore: bool,
+    timeout: Option<Duration>,
+    isolated: bool,
+    retries: Option<usize>,
+    tags: Vec<String>,
+    /// The fixture instances the test would set up.
+    plan: Vec<FixturePlan>,
+    /// Builds the attempts to run if the test fails, with their own (fresh) fixtures.
+    regenerator: Option<Arc<Regenerator>>,
 }
 
 fn setup_gtest() {
@@ -116,54 +207,280 @@ impl Test {
     ) -> Self {
         Self {
             name: name.into(),
-            xfail,
+            combination: None,
+            xfail: xfail.then(XFail::default),
+            should_panic: None,
             ignore,
+            timeout: None,
+            isolated: false,
+            retries: None,
+            tags: Vec::new(),
+            plan: Vec::new(),
+            regenerator: None,
             runner,
         }
     }
-    fn run(self) -> LibTestResult {
+
+    /// Set the name of the fixture combination used by the test.
+    pub fn with_combination(self, combination: Option<String>) -> Self {
+        Self {
+            combination,
+            ..self
+        }
+    }
+
+    /// Set how the test is expected to fail (making it an xfail test).
+    pub fn with_xfail(self, xfail: XFail) -> Self {
+        Self {
+            xfail: Some(xfail),
+            ..self
+        }
+    }
+
+    /// Set that the test must panic, with a message containing `expected` (if any).
+    pub fn with_should_panic(self, expected: Option<String>) -> Self {
+        Self {
+            should_panic: Some(expected),
+            ..self
+        }
+    }
+
+    /// Set the time the test is allowed to run (fixture setup included).
+    ///
+    /// If `None`, the default timeout of the run (if any) applies.
+    pub fn with_timeout(self, timeout: Option<Duration>) -> Self {
+        Self { timeout, ..self }
+    }
+
+    /// Set if the test must be run in its own process.
+    pub fn with_isolated(self, isolated: bool) -> Self {
+        Self { isolated, ..self }
+    }
+
+    /// Set how many times the test is rerun if it fails.
+    ///
+    /// If `None`, the default number of retries of the run applies.
+    pub fn with_retries(self, retries: Option<usize>) -> Self {
+        Self { retries, ..self }
+    }
+
+    /// Set the tags of the test.
+    pub fn with_tags(self, mut tags: Vec<String>) -> Self {
+        tags.sort();
+        tags.dedup();
+        Self { tags, ..self }
+    }
+
+    /// Set the fixture instances the test would set up.
+    pub fn with_plan(self, plan: Vec<FixturePlan>) -> Self {
+        Self { plan, ..self }
+    }
+
+    /// The name of the test (including the fixture combination).
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The name of the fixture combination used by the test (if any).
+    pub fn combination(&self) -> Option<&str> {
+        self.combination.as_deref()
+    }
+
+    /// Is the test expected to fail ?
+    pub fn is_xfail(&self) -> bool {
+        self.xfail.is_some()
+    }
+
+    /// How the test is expected to fail (if it is).
+    pub fn xfail(&self) -> Option<&XFail> {
+        self.xfail.as_ref()
+    }
+
+    /// Is the test ignored ?
+    pub fn is_ignored(&self) -> bool {
+        self.ignore
+    }
+
+    /// The time the test is allowed to run (if it has its own timeout).
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Must the test be run in its own process ?
+    pub fn is_isolated(&self) -> bool {
+        self.isolated
+    }
+
+    /// How many times the test is rerun if it fails (if it has its own number of retries).
+    pub fn retries(&self) -> Option<usize> {
+        self.retries
+    }
+
+    /// The tags of the test, including the tags of the fixtures it uses.
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// The fixture instances the test would set up.
+    pub fn plan(&self) -> &[FixturePlan] {
+        &self.plan
+    }
+
+    /// Set how to build the attempts to run if the test (and previous retries) fails.
+    pub(crate) fn set_regenerator(&mut self, regenerator: Arc<Regenerator>) {
+        self.regenerator = Some(regenerator);
+    }
+
+    pub(crate) fn take_regenerator(&mut self) -> Option<Arc<Regenerator>> {
+        self.regenerator.take()
+    }
+
+    /// The kind of the test, as displayed before its name.
+    pub(crate) fn kind(&self) -> &'static str {
+        if self.is_xfail() { "XFAIL" } else { "" }
+    }
+
+    pub(crate) fn name_with_kind(&self) -> String {
+        if self.is_xfail() {
+            format!("[{}] {}", self.kind(), self.name)
+        } else {
+            self.name.clone()
+        }
+    }
+
+    /// Run the test, reporting the errors of the fixtures it tears down.
+    pub(crate) fn run(self) -> (Outcome, Phases) {
+        event::take_teardown_errors();
+        let (outcome, phases) = self.run_body();
+        (
+            with_teardown_errors(outcome, event::take_teardown_errors()),
+            phases,
+        )
+    }
+
+    fn run_body(self) -> (Outcome, Phases) {
         setup_gtest();
-        let test_runner = (self.runner)()
-            .map_err(|e| Failed::from(format!("Fixture {} error: {}", e.fixture_name, e.error)))?;
+        let fixture_time = event::fixture_time();
+        let start = Instant::now();
+        let runner = (self.runner)();
+        let setup = start.elapsed();
+        let start = Instant::now();
+        let test_runner = match runner {
+            Ok(test_runner) => test_runner,
+            Err(e) => {
+                let phases = phases(fixture_time, setup, Duration::ZERO);
+                let outcome = match e.skip_reason() {
+                    Some(reason) => Outcome::Skipped(reason.to_owned()),
+                    None => Outcome::Error(format!("{e}\n  required by test {}", self.name)),
+                };
+                return (outcome, phases);
+            }
+        };
         let unwind_result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(test_runner));
+        let phases = phases(fixture_time, setup, start.elapsed());
+        let panicked = unwind_result.is_err();
         let test_result = match unwind_result {
             Ok(Ok(())) => Ok(()),
             Ok(Err(e)) => Err(e),
+            Err(cause) if skip::reason(&*cause).is_some() => {
+                let reason = skip::reason(&*cause).unwrap_or_default();
+                return (Outcome::Skipped(reason.to_owned()), phases);
+            }
             Err(cause) => {
-                // We expect the cause payload to be a string or 'str
-                let payload = cause
-                    .downcast_ref::<String>()
-                    .cloned()
-                    .or_else(|| cause.downcast_ref::<&str>().map(|s| s.to_string()))
+                let payload = panic_message(&*cause)
+                    .map(str::to_owned)
                     .unwrap_or(format!("{:?}", cause));
                 Err(InnerTestError::new(payload))
             }
         };
-        let test_result = collect_gtest(test_result);
-        if self.xfail {
-            match test_result {
-                Ok(_) => Err("Test should fail".into()),
-                Err(_) => Ok(()),
-            }
+        // googletest replaces the failure message of a panicking test, `raises` must be
+        // matched against the original one.
+        let failure = test_result.as_ref().err().map(|e| e.to_string());
+        // googletest prints the failure it is given, but the panic hook has already printed the
+        // message of a panic.
+        let test_result = if panicked {
+            collect_gtest(Ok(())).and(test_result)
         } else {
-            Ok(test_result?)
-        }
+            collect_gtest(test_result)
+        };
+        let test_result = test_result.map_err(|e| e.to_string());
+        let test_result = match &self.should_panic {
+            Some(expected) => {
+                let panic = failure.as_deref().filter(|_| panicked);
+                check_panic(expected.as_deref(), panic, test_result)
+            }
+            None => test_result,
+        };
+        let outcome = match (self.xfail, test_result) {
+            (None, Ok(())) => Outcome::Passed,
+            (None, Err(msg)) => Outcome::Failed(msg),
+            (Some(xfail), Ok(())) => Outcome::XPassed {
+                reason: xfail.reason.unwrap_or_default(),
+                strict: xfail.strict,
+            },
+            (Some(xfail), Err(msg)) => match &xfail.raises {
+                Some(raises) if !xfail.matches(failure.as_deref().unwrap_or(&msg)) => {
+                    Outcome::Failed(format!(
+                        "test failed, but not as expected (expected a failure matching `{raises}`): {}",
+                        failure.as_deref().unwrap_or(&msg)
+                    ))
+                }
+                _ => Outcome::XFailed(msg),
+            },
+        };
+        (outcome, phases)
     }
 }
 
-impl From<Test> for libtest_mimic::Trial {
-    fn from(test: Test) -> Self {
-        let xfail = test.xfail;
-        let ignore = test.ignore;
-        let mimic_test = Self::test(test.name.clone(), move || test.run());
+/// The outcome of a test whose fixtures have failed to teardown.
+///
+/// The errors are added to the message of a failing test, otherwise the test is an error.
+fn with_teardown_errors(outcome: Outcome, errors: Vec<String>) -> Outcome {
+    if errors.is_empty() {
+        return outcome;
+    }
+    let errors = errors.join("\n");
+    match outcome {
+        Outcome::Failed(msg) => Outcome::Failed(format!("{msg}\n{errors}")),
+        Outcome::Error(msg) => Outcome::Error(format!("{msg}\n{errors}")),
+        _ => Outcome::Error(errors),
+    }
+}
 
-        let mimic_test = if xfail {
-            mimic_test.with_kind("XFAIL")
-        } else {
-            mimic_test
-        };
+/// The message of a panic, if its payload is a string (as with `panic!`).
+pub(crate) fn panic_message(payload: &(dyn std::any::Any + Send)) -> Option<&str> {
+    payload
+        .downcast_ref::<String>()
+        .map(|s| s.as_str())
+        .or(payload.downcast_ref::<&str>().copied())
+}
+
+/// The result of a `should_panic` test, given the message of its panic (if it has panicked).
+fn check_panic(
+    expected: Option<&str>,
+    panic: Option<&str>,
+    result: std::result::Result<(), String>,
+) -> std::result::Result<(), String> {
+    match (panic, expected) {
+        (Some(msg), Some(expected)) if !msg.contains(expected) => Err(format!(
+            "panic did not contain expected string\n      panic message: {msg:?}\n expected substring: {expected:?}"
+        )),
+        (Some(_), _) => Ok(()),
+        (None, _) => result.and(Err("test did not panic as expected".to_owned())),
+    }
+}
 
-        mimic_test.with_ignored_flag(ignore)
+/// Split the time spent in fixture setup and test body into phases.
+///
+/// Global fixtures are not part of the setup and fixtures are torn down when the test body drops
+/// them, so the time spent in those (since `before`) is moved out of the measured durations.
+fn phases(before: event::FixtureTime, setup: Duration, call: Duration) -> Phases {
+    let after = event::fixture_time();
+    let teardown = after.teardown - before.teardown;
+    Phases {
+        setup: setup.saturating_sub(after.global_setup - before.global_setup),
+        call: call.saturating_sub(teardown),
+        teardown,
     }
 }
 
@@ -173,15 +490,29 @@ impl From<Test> for libtest_mimic::Trial {
 pub struct TestContext<'a> {
     global_reg: &'a mut FixtureRegistry,
     reg: &'a mut FixtureRegistry,
+    /// Params already collected, kept when tests are generated again to be retried.
+    params: &'a mut FixtureRegistry,
+    /// Locations of the fixtures set up by the tests (for `--fixtures`).
+    used_fixtures: &'a mut HashSet<(&'static str, u32)>,
 }
 
 impl<'a> TestContext<'a> {
-    pub(crate) fn new(global_reg: &'a mut FixtureRegistry, reg: &'a mut FixtureRegistry) -> Self {
-        Self { global_reg, reg }
+    pub(crate) fn new(
+        global_reg: &'a mut FixtureRegistry,
+        reg: &'a mut FixtureRegistry,
+        params: &'a mut FixtureRegistry,
+        used_fixtures: &'a mut HashSet<(&'static str, u32)>,
+    ) -> Self {
+        Self {
+            global_reg,
+            reg,
+            params,
+            used_fixtures,
+        }
     }
     pub fn add<B>(&mut self, value: Vec<B>)
     where
-        B: FixtureProxy + 'static,
+        B: FixtureProxy + Send + 'static,
     {
         let reg = match B::SCOPE {
             FixtureScope::Test => &mut self.reg,
@@ -193,7 +524,7 @@ impl<'a> TestContext<'a> {
 
     pub fn get<B>(&mut self) -> Option<Vec<B>>
     where
-        B: FixtureProxy + 'static,
+        B: FixtureProxy + Send + 'static,
     {
         let reg = match B::SCOPE {
             FixtureScope::Test => &mut self.reg,
@@ -203,10 +534,71 @@ impl<'a> TestContext<'a> {
         reg.get::<B>()
     }
 
+    /// Setup the proxies of a fixture, recording the fixture as used.
     pub fn get_fixture<Fix>(&mut self) -> Vec<Fix>
     where
         Fix: FixtureProxy + Any,
     {
+        if let Some(location) = Fix::location() {
+            self.used_fixtures.insert(location);
+        }
         Fix::setup(self)
     }
+
+    /// The proxies of params, collected with `collect` the first time they are set up.
+    pub fn params<B>(&mut self, collect: impl FnOnce() -> Vec<B>) -> Vec<B>
+    where
+        B: FixtureProxy + Send + 'static,
+    {
+        if let Some(params) = self.params.get() {
+            return params;
+        }
+        let params = collect();
+        self.params.add::<B>(params.duplicate());
+        params
+    }
+}
+
+/// Generates the tests of a test function again, to retry the failing ones.
+///
+/// The tests are generated with fresh fixtures, except for the global ones, and with the params
+/// collected the first time.
+pub(crate) struct Regenerator {
+    generator: TestGeneratorFn,
+    global_reg: Arc<Mutex<FixtureRegistry>>,
+    params: Mutex<FixtureRegistry>,
+}
+
+impl Regenerator {
+    pub(crate) fn new(
+        generator: TestGeneratorFn,
+        global_reg: Arc<Mutex<FixtureRegistry>>,
+        params: FixtureRegistry,
+    ) -> Self {
+        Self {
+            generator,
+            global_reg,
+            params: Mutex::new(params),
+        }
+    }
+
+    /// Generate the test `name` again, if its generator still builds it.
+    pub(crate) fn generate(&self, name: &str) -> Option<Test> {
+        let mut global_reg = self
+            .global_reg
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let mut params = self.params.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut test_reg = FixtureRegistry::new();
+        let mut used_fixtures = HashSet::new();
+        let mut ctx = TestContext::new(
+            &mut global_reg,
+            &mut test_reg,
+            &mut params,
+            &mut used_fixtures,
+        );
+        (self.generator)(&mut ctx)
+            .into_iter()
+            .find(|test| test.name() == name)
+    }
 }
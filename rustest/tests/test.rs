@@ -50,6 +50,17 @@ fn test_fixture_number(number: ANumber) {
     assert_eq!(*number, 5)
 }
 
+// Fixtures may differ only by the case of their name.
+#[fixture]
+fn Anumber() -> u32 {
+    7
+}
+
+#[test]
+fn test_fixture_name_case(number: ANumber, other: Anumber) {
+    assert_eq!((*number, *other), (5, 7))
+}
+
 // Fixture's name can be specified with the `name` attribute.
 // The function's name is useless in this case and can be anything.
 #[fixture(name = ANewNumber)]